@@ -1,40 +1,48 @@
+use crate::Part;
 use anyhow::{anyhow, Result};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 use std::path::Path;
 
+/// Return the digit that starts at the given byte offset, if any
+fn digit_at(s: &str, i: usize, include_named: bool) -> Option<usize> {
+    if s[i..].starts_with('1') || (include_named && s[i..].starts_with("one")) {
+        Some(1)
+    } else if s[i..].starts_with('2') || (include_named && s[i..].starts_with("two")) {
+        Some(2)
+    } else if s[i..].starts_with('3') || (include_named && s[i..].starts_with("three")) {
+        Some(3)
+    } else if s[i..].starts_with('4') || (include_named && s[i..].starts_with("four")) {
+        Some(4)
+    } else if s[i..].starts_with('5') || (include_named && s[i..].starts_with("five")) {
+        Some(5)
+    } else if s[i..].starts_with('6') || (include_named && s[i..].starts_with("six")) {
+        Some(6)
+    } else if s[i..].starts_with('7') || (include_named && s[i..].starts_with("seven")) {
+        Some(7)
+    } else if s[i..].starts_with('8') || (include_named && s[i..].starts_with("eight")) {
+        Some(8)
+    } else if s[i..].starts_with('9') || (include_named && s[i..].starts_with("nine")) {
+        Some(9)
+    } else {
+        None
+    }
+}
+
 fn find_all_digits(s: &str, include_named: bool) -> impl Iterator<Item = usize> + '_ {
-    (0..s.len()).filter_map(move |i| {
-        if s[i..].starts_with('1') || (include_named && s[i..].starts_with("one")) {
-            Some(1)
-        } else if s[i..].starts_with('2') || (include_named && s[i..].starts_with("two")) {
-            Some(2)
-        } else if s[i..].starts_with('3') || (include_named && s[i..].starts_with("three")) {
-            Some(3)
-        } else if s[i..].starts_with('4') || (include_named && s[i..].starts_with("four")) {
-            Some(4)
-        } else if s[i..].starts_with('5') || (include_named && s[i..].starts_with("five")) {
-            Some(5)
-        } else if s[i..].starts_with('6') || (include_named && s[i..].starts_with("six")) {
-            Some(6)
-        } else if s[i..].starts_with('7') || (include_named && s[i..].starts_with("seven")) {
-            Some(7)
-        } else if s[i..].starts_with('8') || (include_named && s[i..].starts_with("eight")) {
-            Some(8)
-        } else if s[i..].starts_with('9') || (include_named && s[i..].starts_with("nine")) {
-            Some(9)
-        } else {
-            None
-        }
-    })
+    (0..s.len()).filter_map(move |i| digit_at(s, i, include_named))
+}
+
+/// Find the digit that starts last in the string by scanning suffixes from the right
+fn find_last_digit(s: &str, include_named: bool) -> Option<usize> {
+    (0..s.len())
+        .rev()
+        .find_map(|i| digit_at(s, i, include_named))
 }
 
 fn find_calibration_value(line: &str, include_named: bool) -> Result<usize> {
     let first = find_all_digits(line, include_named)
         .next()
         .ok_or_else(|| anyhow!("Unable to parse first digit {:?}", line))?;
-    let second = find_all_digits(line, include_named)
-        .last()
+    let second = find_last_digit(line, include_named)
         .ok_or_else(|| anyhow!("Unable to parse second digit {:?}", line))?;
     Ok(10 * first + second)
 }
@@ -46,16 +54,31 @@ fn calibration_value_sum(lines: &[String], include_named: bool) -> Result<usize>
         .sum()
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let file = File::open(path)?;
-    let lines = BufReader::new(file)
-        .lines()
-        .collect::<Result<Vec<String>, _>>()?;
+/// Parse the puzzle input
+pub fn parse(input: &str) -> Result<Vec<String>> {
+    Ok(input.lines().map(ToOwned::to_owned).collect())
+}
 
-    Ok((
-        calibration_value_sum(&lines, false)?,
-        Some(calibration_value_sum(&lines, true)?),
-    ))
+/// Solve both parts using the parsed puzzle input
+pub fn solve(lines: &[String], part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part
+        .includes_a()
+        .then(|| calibration_value_sum(lines, false))
+        .transpose()?;
+    let b = part
+        .includes_b()
+        .then(|| calibration_value_sum(lines, true))
+        .transpose()?;
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let lines = parse(input)?;
+    solve(&lines, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -70,6 +93,20 @@ mod test {
         assert_eq!(find_calibration_value("sevenine", true).unwrap(), 79);
     }
 
+    #[test]
+    fn test_find_last_digit() {
+        let line = format!("7{}twone{}", "x".repeat(100_000), "a".repeat(100_000));
+        for include_named in [false, true] {
+            assert_eq!(
+                find_last_digit(&line, include_named),
+                find_all_digits(&line, include_named).last()
+            );
+        }
+        assert_eq!(find_last_digit(&line, true), Some(1));
+        assert_eq!(find_last_digit(&line, false), Some(7));
+        assert_eq!(find_last_digit("abc", true), None);
+    }
+
     #[test]
     fn test_part_a() {
         let input = ["1abc2", "pqr3stu8vwx", "a1b2c3d4e5f", "treb7uchet"]
@@ -1,9 +1,10 @@
+use crate::Part;
 use anyhow::{anyhow, Result};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Coordinate {
+pub struct Coordinate {
     x: isize,
     y: isize,
 }
@@ -17,7 +18,7 @@ enum Direction {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Pipe {
+pub enum Pipe {
     NorthSouth,
     WestEast,
     NorthWest,
@@ -27,7 +28,7 @@ enum Pipe {
 }
 
 impl Coordinate {
-    fn new(x: isize, y: isize) -> Self {
+    pub fn new(x: isize, y: isize) -> Self {
         Self { x, y }
     }
 
@@ -73,7 +74,12 @@ impl Pipe {
     }
 }
 
-fn find_loop_path(start: Coordinate, pipes: &HashMap<Coordinate, Pipe>) -> Option<Vec<Coordinate>> {
+/// Follow the loop going through the start position. Returns the path in walking order along with
+/// the set of tiles on it
+pub fn find_loop_path(
+    start: Coordinate,
+    pipes: &HashMap<Coordinate, Pipe>,
+) -> Option<(Vec<Coordinate>, HashSet<Coordinate>)> {
     let mut move_direction = pipes.get(&start).map(|p| match p {
         Pipe::NorthSouth => Direction::South,
         Pipe::WestEast => Direction::East,
@@ -84,9 +90,10 @@ fn find_loop_path(start: Coordinate, pipes: &HashMap<Coordinate, Pipe>) -> Optio
     })?;
     let mut pos = start;
     let mut path = Vec::new();
+    let mut on_path = HashSet::new();
     while let Some(pipe) = pipes.get(&pos) {
-        if path.contains(&pos) {
-            return Some(path);
+        if !on_path.insert(pos) {
+            return Some((path, on_path));
         }
         path.push(pos);
 
@@ -100,30 +107,41 @@ fn find_loop_path(start: Coordinate, pipes: &HashMap<Coordinate, Pipe>) -> Optio
     None
 }
 
+/// Return the top left and bottom right corners of the smallest rectangle containing the loop
+/// path. Both corners are inclusive
+pub fn loop_bounds(path: &[Coordinate]) -> ((isize, isize), (isize, isize)) {
+    let min_x = path.iter().map(|c| c.x).min().unwrap_or(0);
+    let max_x = path.iter().map(|c| c.x).max().unwrap_or(0);
+    let min_y = path.iter().map(|c| c.y).min().unwrap_or(0);
+    let max_y = path.iter().map(|c| c.y).max().unwrap_or(0);
+    ((min_x, min_y), (max_x, max_y))
+}
+
 fn part_a(start: Coordinate, pipes: &HashMap<Coordinate, Pipe>) -> Result<usize> {
-    let path = find_loop_path(start, pipes).ok_or_else(|| anyhow!("Unable to find loop"))?;
+    let (path, _) = find_loop_path(start, pipes).ok_or_else(|| anyhow!("Unable to find loop"))?;
     Ok(path.len() / 2)
 }
 
-fn part_b(start: Coordinate, pipes: &HashMap<Coordinate, Pipe>) -> Result<usize> {
-    let path = find_loop_path(start, pipes).ok_or_else(|| anyhow!("Unable to find loop"))?;
+/// Return every tile that is enclosed by the loop going through the start position. Returns an
+/// empty set if there is no loop
+pub fn enclosed_tiles(start: Coordinate, pipes: &HashMap<Coordinate, Pipe>) -> HashSet<Coordinate> {
+    let Some((path, on_loop)) = find_loop_path(start, pipes) else {
+        return HashSet::new();
+    };
 
     // Pipes that flip whether or not we are inside the enclosed loop
     let special_pipes = [Pipe::NorthSouth, Pipe::NorthEast, Pipe::NorthWest];
 
-    let min_x = path.iter().map(|c| c.x).min().unwrap();
-    let max_x = path.iter().map(|c| c.x).max().unwrap();
-    let min_y = path.iter().map(|c| c.y).min().unwrap();
-    let max_y = path.iter().map(|c| c.y).max().unwrap();
+    let ((min_x, min_y), (max_x, max_y)) = loop_bounds(&path);
 
-    let mut num_inside = 0;
+    let mut inside = HashSet::new();
     for y in min_y..=max_y {
         let mut is_inside = false;
         for x in min_x..=max_x {
             let c = Coordinate::new(x, y);
-            if !path.contains(&c) {
+            if !on_loop.contains(&c) {
                 if is_inside {
-                    num_inside += 1;
+                    inside.insert(c);
                 }
                 continue;
             }
@@ -135,10 +153,33 @@ fn part_b(start: Coordinate, pipes: &HashMap<Coordinate, Pipe>) -> Result<usize>
             }
         }
     }
-    Ok(num_inside)
+    inside
+}
+
+/// Return twice the area of the polygon that has the coordinates of the closed path as its
+/// corners, using the shoelace formula
+fn double_area(path: &[Coordinate]) -> usize {
+    let next = path.iter().cycle().skip(1);
+    path.iter()
+        .zip(next)
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum::<isize>()
+        .unsigned_abs()
+}
+
+fn part_b(start: Coordinate, pipes: &HashMap<Coordinate, Pipe>) -> Result<usize> {
+    let (path, _) = find_loop_path(start, pipes).ok_or_else(|| anyhow!("Unable to find loop"))?;
+
+    // Every tile of the loop is a lattice point on the boundary of the polygon, so Pick's theorem
+    // (A = i + b/2 - 1) gives us the number of tiles inside of it. A loop that encloses nothing
+    // has 2A - b = -2, so this is done with signed integers
+    let double_area: isize = double_area(&path).try_into()?;
+    let boundary: isize = path.len().try_into()?;
+    Ok(((double_area - boundary + 2) / 2).try_into()?)
 }
 
-fn parse_pipes(s: &str) -> Result<(Coordinate, HashMap<Coordinate, Pipe>)> {
+/// Parse the puzzle input
+pub fn parse(s: &str) -> Result<(Coordinate, HashMap<Coordinate, Pipe>)> {
     let mut start = None;
     let mut pipes = HashMap::new();
     for (y, line) in s.lines().enumerate() {
@@ -166,8 +207,14 @@ fn parse_pipes(s: &str) -> Result<(Coordinate, HashMap<Coordinate, Pipe>)> {
         }
     }
 
-    // Determine type of the start pipe
     let start = start.ok_or_else(|| anyhow!("No starting position found"))?;
+    let start_pipe = infer_start_pipe(start, &pipes)?;
+    pipes.insert(start, start_pipe);
+    Ok((start, pipes))
+}
+
+/// Determine the type of pipe at the starting position from the pipes that connect to it
+pub fn infer_start_pipe(start: Coordinate, pipes: &HashMap<Coordinate, Pipe>) -> Result<Pipe> {
     for start_pipe in [
         Pipe::NorthSouth,
         Pipe::WestEast,
@@ -185,8 +232,7 @@ fn parse_pipes(s: &str) -> Result<(Coordinate, HashMap<Coordinate, Pipe>)> {
                     .is_some()
             });
         if is_valid_start_pipe {
-            pipes.insert(start, start_pipe);
-            return Ok((start, pipes));
+            return Ok(start_pipe);
         }
     }
     Err(anyhow!(
@@ -194,10 +240,29 @@ fn parse_pipes(s: &str) -> Result<(Coordinate, HashMap<Coordinate, Pipe>)> {
     ))
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let file = std::fs::read_to_string(path)?;
-    let (start, pipes) = parse_pipes(&file)?;
-    Ok((part_a(start, &pipes)?, part_b(start, &pipes)?.into()))
+/// Solve both parts using the parsed puzzle input
+pub fn solve(
+    (start_pos, pipes): &(Coordinate, HashMap<Coordinate, Pipe>),
+    part: Part,
+) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part
+        .includes_a()
+        .then(|| part_a(*start_pos, pipes))
+        .transpose()?;
+    let b = part
+        .includes_b()
+        .then(|| part_b(*start_pos, pipes))
+        .transpose()?;
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let parsed = parse(input)?;
+    solve(&parsed, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -207,7 +272,7 @@ mod test {
     test_real_input!(10, 6757, 523);
 
     #[rustfmt::skip]
-    const EXAMPLE_1_A: &'static str = concat!(
+    const EXAMPLE_1_A: &str = concat!(
         ".....\n",
         ".S-7.\n",
         ".|.|.\n",
@@ -216,7 +281,7 @@ mod test {
     );
 
     #[rustfmt::skip]
-    const EXAMPLE_1_B: &'static str = concat!(
+    const EXAMPLE_1_B: &str = concat!(
         "..F7.\n",
         ".FJ|.\n",
         "SJ.L7\n",
@@ -224,7 +289,7 @@ mod test {
         "LJ...\n",
     );
 
-    const EXAMPLE_2_A: &'static str = concat!(
+    const EXAMPLE_2_A: &str = concat!(
         "...........\n",
         ".S-------7.\n",
         ".|F-----7|.\n",
@@ -236,7 +301,7 @@ mod test {
         "...........\n",
     );
 
-    const EXAMPLE_2_B: &'static str = concat!(
+    const EXAMPLE_2_B: &str = concat!(
         "..........\n",
         ".S------7.\n",
         ".|F----7|.\n",
@@ -248,7 +313,7 @@ mod test {
         "..........\n",
     );
 
-    const EXAMPLE_2_C: &'static str = concat!(
+    const EXAMPLE_2_C: &str = concat!(
         ".F----7F7F7F7F-7....\n",
         ".|F--7||||||||FJ....\n",
         ".||.FJ||||||||L7....\n",
@@ -261,7 +326,7 @@ mod test {
         "....L---J.LJ.LJLJ...\n",
     );
 
-    const EXAMPLE_2_D: &'static str = concat!(
+    const EXAMPLE_2_D: &str = concat!(
         "FF7FSF7F7F7F7F7F---7\n",
         "L|LJ||||||||||||F--J\n",
         "FL-7LJLJ||||||LJL-77\n",
@@ -277,31 +342,96 @@ mod test {
     #[test]
     fn test_part_a() {
         {
-            let (start, pipes) = parse_pipes(EXAMPLE_1_A).unwrap();
+            let (start, pipes) = parse(EXAMPLE_1_A).unwrap();
             assert_eq!(part_a(start, &pipes).unwrap(), 4);
         }
         {
-            let (start, pipes) = parse_pipes(EXAMPLE_1_B).unwrap();
+            let (start, pipes) = parse(EXAMPLE_1_B).unwrap();
             assert_eq!(part_a(start, &pipes).unwrap(), 8);
         }
     }
 
+    #[test]
+    fn test_infer_start_pipe() {
+        let (start, mut pipes) = parse(EXAMPLE_1_A).unwrap();
+        assert_eq!(start, Coordinate::new(1, 1));
+        assert_eq!(pipes[&start], Pipe::SouthEast);
+
+        // The inference doesn't depend on the start pipe already being known
+        pipes.remove(&start);
+        assert_eq!(infer_start_pipe(start, &pipes).unwrap(), Pipe::SouthEast);
+        assert!(infer_start_pipe(Coordinate::new(0, 0), &pipes).is_err());
+    }
+
+    #[test]
+    fn test_loop_queries() {
+        let (start, pipes) = parse(EXAMPLE_1_A).unwrap();
+        let (path, on_loop) = find_loop_path(start, &pipes).unwrap();
+        assert!(on_loop.contains(&Coordinate::new(3, 2)));
+        assert!(!on_loop.contains(&Coordinate::new(2, 2)));
+        assert!(!on_loop.contains(&Coordinate::new(0, 0)));
+        assert_eq!(on_loop.len(), path.len());
+        assert_eq!(loop_bounds(&path), ((1, 1), (3, 3)));
+    }
+
+    #[test]
+    fn test_enclosed_tiles() {
+        let (start, pipes) = parse(EXAMPLE_2_A).unwrap();
+        let tiles = enclosed_tiles(start, &pipes);
+        assert_eq!(tiles.len(), 4);
+
+        let (_, on_loop) = find_loop_path(start, &pipes).unwrap();
+        assert!(tiles.iter().all(|&c| !on_loop.contains(&c)));
+        assert!(tiles.contains(&Coordinate::new(2, 6)));
+
+        // The scanline agrees with the area based count of part B
+        for example in [EXAMPLE_2_B, EXAMPLE_2_C, EXAMPLE_2_D] {
+            let (start, pipes) = parse(example).unwrap();
+            assert_eq!(
+                enclosed_tiles(start, &pipes).len(),
+                part_b(start, &pipes).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_double_area() {
+        let (start, pipes) = parse(EXAMPLE_1_A).unwrap();
+        let (path, _) = find_loop_path(start, &pipes).unwrap();
+        assert_eq!(double_area(&path), 8);
+
+        // The direction the loop is followed in doesn't matter
+        let reversed = path.iter().rev().copied().collect::<Vec<_>>();
+        assert_eq!(double_area(&reversed), 8);
+    }
+
+    #[test]
+    fn test_nothing_enclosed() {
+        let input = ".....\n.S-7.\n.L-J.\n.....\n";
+        let (start, pipes) = parse(input).unwrap();
+        let (path, _) = find_loop_path(start, &pipes).unwrap();
+        assert_eq!(double_area(&path), 4);
+        assert_eq!(path.len(), 6);
+        assert_eq!(part_b(start, &pipes).unwrap(), 0);
+        assert!(enclosed_tiles(start, &pipes).is_empty());
+    }
+
     #[test]
     fn test_part_b() {
         {
-            let (start, pipes) = parse_pipes(EXAMPLE_2_A).unwrap();
+            let (start, pipes) = parse(EXAMPLE_2_A).unwrap();
             assert_eq!(part_b(start, &pipes).unwrap(), 4);
         }
         {
-            let (start, pipes) = parse_pipes(EXAMPLE_2_B).unwrap();
+            let (start, pipes) = parse(EXAMPLE_2_B).unwrap();
             assert_eq!(part_b(start, &pipes).unwrap(), 4);
         }
         {
-            let (start, pipes) = parse_pipes(EXAMPLE_2_C).unwrap();
+            let (start, pipes) = parse(EXAMPLE_2_C).unwrap();
             assert_eq!(part_b(start, &pipes).unwrap(), 8);
         }
         {
-            let (start, pipes) = parse_pipes(EXAMPLE_2_D).unwrap();
+            let (start, pipes) = parse(EXAMPLE_2_D).unwrap();
             assert_eq!(part_b(start, &pipes).unwrap(), 10);
         }
     }
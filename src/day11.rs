@@ -1,8 +1,30 @@
+use crate::Part;
 use anyhow::{anyhow, Result};
 use std::collections::HashSet;
 use std::path::Path;
 
-fn parse_galaxies(s: &str) -> Result<HashSet<(usize, usize)>> {
+/// How to measure the distance between two galaxies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Number of steps when only moving horizontally or vertically
+    Manhattan,
+    /// Number of steps when diagonal moves are allowed as well
+    Chebyshev,
+}
+
+impl Metric {
+    fn distance(&self, a: (usize, usize), b: (usize, usize)) -> usize {
+        let dx = a.0.abs_diff(b.0);
+        let dy = a.1.abs_diff(b.1);
+        match self {
+            Self::Manhattan => dx + dy,
+            Self::Chebyshev => dx.max(dy),
+        }
+    }
+}
+
+/// Parse the puzzle input
+pub fn parse(s: &str) -> Result<HashSet<(usize, usize)>> {
     let mut map = HashSet::new();
     for (y, line) in s.lines().enumerate() {
         for (x, c) in line.chars().enumerate() {
@@ -27,6 +49,17 @@ fn parse_galaxies(s: &str) -> Result<HashSet<(usize, usize)>> {
     Ok(map)
 }
 
+/// Return the number of voids before every index, given whether each index is void
+fn void_counts_before(is_void: impl Iterator<Item = bool>) -> Vec<usize> {
+    is_void
+        .scan(0, |num_voids, v| {
+            let before = *num_voids;
+            *num_voids += usize::from(v);
+            Some(before)
+        })
+        .collect()
+}
+
 fn expand_void(
     galaxies: &HashSet<(usize, usize)>,
     void_expansion_factor: usize,
@@ -38,32 +71,28 @@ fn expand_void(
     let max_x = galaxies.iter().copied().map(|(x, _)| x).max().unwrap_or(0);
     let max_y = galaxies.iter().copied().map(|(_, y)| y).max().unwrap_or(0);
 
-    let void_columns = (0..=max_x)
-        .filter(|&x| (0..=max_y).all(move |y| !galaxies.contains(&(x, y))))
-        .collect::<Vec<_>>();
-    let void_rows = (0..=max_y)
-        .filter(|&y| (0..=max_x).all(move |x| !galaxies.contains(&(x, y))))
-        .collect::<Vec<_>>();
+    let is_void_column = |x| (0..=max_y).all(|y| !galaxies.contains(&(x, y)));
+    let is_void_row = |y| (0..=max_x).all(|x| !galaxies.contains(&(x, y)));
+
+    // The number of void columns to the left of each column, and void rows above each row
+    let void_columns_before = void_counts_before((0..=max_x).map(is_void_column));
+    let void_rows_before = void_counts_before((0..=max_y).map(is_void_row));
 
     let mut expanded_galaxies = HashSet::new();
     for (x, y) in galaxies.iter().copied() {
-        let num_void_columns = void_columns
-            .iter()
-            .take_while(|x_limit| &x > x_limit)
-            .count();
-        let num_void_rows = void_rows.iter().take_while(|y_limit| &y > y_limit).count();
         expanded_galaxies.insert((
-            x + num_void_columns * (void_expansion_factor - 1),
-            y + num_void_rows * (void_expansion_factor - 1),
+            x + void_columns_before[x] * (void_expansion_factor - 1),
+            y + void_rows_before[y] * (void_expansion_factor - 1),
         ));
     }
 
     Ok(expanded_galaxies)
 }
 
-fn sum_pairwise_distances(
+pub fn sum_pairwise_distances(
     galaxies: &HashSet<(usize, usize)>,
     void_expansion_factor: usize,
+    metric: Metric,
 ) -> Result<usize> {
     let mut expanded_galaxies = expand_void(galaxies, void_expansion_factor)?
         .into_iter()
@@ -72,19 +101,44 @@ fn sum_pairwise_distances(
     let mut sum = 0;
     while let Some(a) = expanded_galaxies.pop() {
         for b in expanded_galaxies.iter().copied() {
-            sum += a.0.abs_diff(b.0) + a.1.abs_diff(b.1);
+            sum += metric.distance(a, b);
         }
     }
     Ok(sum)
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let map_str = std::fs::read_to_string(path)?;
-    let galaxies = parse_galaxies(&map_str)?;
-    Ok((
-        sum_pairwise_distances(&galaxies, 2)?,
-        sum_pairwise_distances(&galaxies, 1_000_000)?.into(),
-    ))
+/// Solve both parts using the parsed puzzle input
+pub fn solve(
+    galaxies: &HashSet<(usize, usize)>,
+    part: Part,
+) -> Result<(Option<usize>, Option<usize>)> {
+    solve_with_expansion(galaxies, part, 1_000_000)
+}
+
+/// Solve both parts like `solve`, but expand empty space by the given factor in part B
+pub fn solve_with_expansion(
+    galaxies: &HashSet<(usize, usize)>,
+    part: Part,
+    expansion_factor: usize,
+) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part
+        .includes_a()
+        .then(|| sum_pairwise_distances(galaxies, 2, Metric::Manhattan))
+        .transpose()?;
+    let b = part
+        .includes_b()
+        .then(|| sum_pairwise_distances(galaxies, expansion_factor, Metric::Manhattan))
+        .transpose()?;
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let galaxies = parse(input)?;
+    solve(&galaxies, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -93,7 +147,7 @@ mod test {
 
     test_real_input!(11, 9_686_930, 630_728_425_490);
 
-    const EXAMPLE_A: &'static str = concat!(
+    const EXAMPLE_A: &str = concat!(
         "...#......\n",
         ".......#..\n",
         "#.........\n",
@@ -106,7 +160,7 @@ mod test {
         "#...#.....\n",
     );
 
-    const EXAMPLE_A_EXPANDED: &'static str = concat!(
+    const EXAMPLE_A_EXPANDED: &str = concat!(
         "....#........\n",
         ".........#...\n",
         "#............\n",
@@ -121,19 +175,57 @@ mod test {
         "#....#.......\n",
     );
 
+    #[test]
+    fn test_void_counts_before() {
+        let is_void = [false, true, true, false, true, false];
+        assert_eq!(
+            void_counts_before(is_void.into_iter()),
+            vec![0, 0, 1, 2, 2, 3]
+        );
+    }
+
     #[test]
     fn test_expand_void() {
         assert_eq!(
-            expand_void(&parse_galaxies(EXAMPLE_A).unwrap(), 2).unwrap(),
-            parse_galaxies(EXAMPLE_A_EXPANDED).unwrap(),
+            expand_void(&parse(EXAMPLE_A).unwrap(), 2).unwrap(),
+            parse(EXAMPLE_A_EXPANDED).unwrap(),
         );
     }
 
     #[test]
     fn test_sum_distances() {
-        let galaxies = parse_galaxies(EXAMPLE_A).unwrap();
-        assert_eq!(sum_pairwise_distances(&galaxies, 2).unwrap(), 374);
-        assert_eq!(sum_pairwise_distances(&galaxies, 10).unwrap(), 1030);
-        assert_eq!(sum_pairwise_distances(&galaxies, 100).unwrap(), 8410);
+        let galaxies = parse(EXAMPLE_A).unwrap();
+        assert_eq!(
+            sum_pairwise_distances(&galaxies, 2, Metric::Manhattan).unwrap(),
+            374
+        );
+        assert_eq!(
+            sum_pairwise_distances(&galaxies, 10, Metric::Manhattan).unwrap(),
+            1030
+        );
+        assert_eq!(
+            sum_pairwise_distances(&galaxies, 100, Metric::Manhattan).unwrap(),
+            8410
+        );
+    }
+
+    #[test]
+    fn test_chebyshev_distance() {
+        // The empty middle column is doubled, placing the galaxies at (0, 0) and (3, 1)
+        let galaxies = parse("#..\n..#\n").unwrap();
+        assert_eq!(
+            sum_pairwise_distances(&galaxies, 2, Metric::Manhattan).unwrap(),
+            4
+        );
+        assert_eq!(
+            sum_pairwise_distances(&galaxies, 2, Metric::Chebyshev).unwrap(),
+            3
+        );
+
+        let galaxies = parse(EXAMPLE_A).unwrap();
+        assert_eq!(
+            sum_pairwise_distances(&galaxies, 2, Metric::Chebyshev).unwrap(),
+            266
+        );
     }
 }
@@ -1,17 +1,17 @@
+use crate::Part;
 use anyhow::{anyhow, Result};
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::BufRead;
 use std::path::Path;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Condition {
+pub enum Condition {
     Unknown,
     Operational,
     Damaged,
 }
 
-type Record = (Vec<Condition>, Vec<usize>);
+pub type Record = (Vec<Condition>, Vec<usize>);
 
 fn parse_record(s: &str) -> Result<Record> {
     let Some((springs_str, groups_str)) = s.split_once(' ') else {
@@ -37,56 +37,51 @@ fn parse_record(s: &str) -> Result<Record> {
     Ok((springs, damaged_groups))
 }
 
-// This has a horrible signature because Rust doesn't really have anything like Python's LRU cache
-// :(
+/// Count the configurations of the springs from `spring_idx` onwards that match the damaged groups
+/// from `group_idx` onwards, when the springs right before `spring_idx` are a streak of damaged
+/// springs of the given length. The memo is only valid for a single set of springs and groups
 fn memoized_num_cfgs(
-    memo: &mut HashMap<(Vec<Condition>, Vec<usize>, usize), usize>,
+    memo: &mut HashMap<(usize, usize, usize), u128>,
     springs: &[Condition],
-    cfg: &[usize],
+    groups: &[usize],
+    spring_idx: usize,
+    group_idx: usize,
     damaged_streak: usize,
-) -> usize {
+) -> u128 {
     // Try to retrieve the output from the cache
-    let cache_key = (springs.to_vec(), cfg.to_vec(), damaged_streak);
+    let cache_key = (spring_idx, group_idx, damaged_streak);
     if let Some(&n) = memo.get(&cache_key) {
         return n;
     }
 
     // We had a cache miss and need to compute the number of configs
-    let num_cfgs = {
-        let max_damaged_streak = cfg.first().copied().unwrap_or(0);
-        match springs.get(0).copied() {
-            Some(Condition::Unknown) => {
-                // Branch out to try all options
-                let mut when_operational = springs.to_vec();
-                when_operational[0] = Condition::Operational;
-
-                let mut when_damaged = springs.to_vec();
-                when_damaged[0] = Condition::Damaged;
-
-                memoized_num_cfgs(memo, &when_operational, cfg, damaged_streak)
-                    + memoized_num_cfgs(memo, &when_damaged, cfg, damaged_streak)
-            }
-            Some(Condition::Operational) => {
-                if damaged_streak == 0 {
-                    memoized_num_cfgs(memo, &springs[1..], cfg, 0)
-                } else if damaged_streak != max_damaged_streak {
-                    0
-                } else {
-                    memoized_num_cfgs(memo, &springs[1..], &cfg[1..], 0)
-                }
-            }
-            Some(Condition::Damaged) => {
-                if damaged_streak >= max_damaged_streak {
-                    0
-                } else {
-                    memoized_num_cfgs(memo, &springs[1..], cfg, damaged_streak + 1)
-                }
-            }
-            None => {
-                // We have reached the end of the list of springs, so we must either have no damage
-                // streak or exactly fulfill the current damage_streak
-                usize::from(cfg.len() <= 1 && max_damaged_streak == damaged_streak)
-            }
+    let max_damaged_streak = groups.get(group_idx).copied().unwrap_or(0);
+    let when_operational = |memo: &mut _| {
+        if damaged_streak == 0 {
+            memoized_num_cfgs(memo, springs, groups, spring_idx + 1, group_idx, 0)
+        } else if damaged_streak != max_damaged_streak {
+            0
+        } else {
+            memoized_num_cfgs(memo, springs, groups, spring_idx + 1, group_idx + 1, 0)
+        }
+    };
+    let when_damaged = |memo: &mut _| {
+        if damaged_streak >= max_damaged_streak {
+            0
+        } else {
+            let streak = damaged_streak + 1;
+            memoized_num_cfgs(memo, springs, groups, spring_idx + 1, group_idx, streak)
+        }
+    };
+    let num_cfgs = match springs.get(spring_idx) {
+        // Branch out to try both options
+        Some(Condition::Unknown) => when_operational(memo) + when_damaged(memo),
+        Some(Condition::Operational) => when_operational(memo),
+        Some(Condition::Damaged) => when_damaged(memo),
+        None => {
+            // We have reached the end of the list of springs, so we must either have no damage
+            // streak or exactly fulfill the current damage_streak
+            u128::from(groups.len() - group_idx <= 1 && max_damaged_streak == damaged_streak)
         }
     };
 
@@ -95,43 +90,88 @@ fn memoized_num_cfgs(
     num_cfgs
 }
 
-fn part_a(records: &[Record]) -> usize {
-    let mut memo = HashMap::new();
-    let mut num_cfgs = 0;
-    for (conditions, cfg) in records.iter() {
-        num_cfgs += memoized_num_cfgs(&mut memo, conditions, cfg, 0);
+/// Count the configurations of the springs that match the damaged groups
+fn num_cfgs(springs: &[Condition], groups: &[usize]) -> u128 {
+    memoized_num_cfgs(&mut HashMap::new(), springs, groups, 0, 0, 0)
+}
+
+fn part_a(records: &[Record]) -> u128 {
+    records
+        .iter()
+        .map(|(conditions, cfg)| num_cfgs(conditions, cfg))
+        .sum()
+}
+
+/// Repeat the record the given number of times, separating the springs of each copy by an unknown
+pub fn unfold((conditions, cfg): &Record, copies: usize) -> Record {
+    let mut extended_conditions = conditions.clone();
+    let mut extended_cfg = cfg.clone();
+    for _ in 1..copies {
+        extended_conditions.push(Condition::Unknown);
+        extended_conditions.extend(conditions.iter().copied());
+        extended_cfg.extend(cfg.iter().copied());
     }
-    num_cfgs
+    (extended_conditions, extended_cfg)
 }
 
-fn part_b(records: &[Record]) -> usize {
-    let mut memo = HashMap::new();
-    let mut num_cfgs = 0;
-    for (conditions, cfg) in records.iter() {
-        let mut extended_conditions = conditions.clone();
-        let mut extended_cfg = cfg.clone();
-        for _ in 0..4 {
-            extended_conditions.push(Condition::Unknown);
-            extended_conditions.extend(conditions.iter().copied());
-            extended_cfg.extend(cfg.iter().copied());
-        }
-        num_cfgs += memoized_num_cfgs(&mut memo, &extended_conditions, &extended_cfg, 0);
+fn part_b(records: &[Record]) -> u128 {
+    records
+        .iter()
+        .map(|record| {
+            let (extended_conditions, extended_cfg) = unfold(record, 5);
+            num_cfgs(&extended_conditions, &extended_cfg)
+        })
+        .sum()
+}
+
+/// Count the arrangements of a single record line after unfolding it the given number of times
+pub fn num_arrangements(s: &str, copies: usize) -> Result<u128> {
+    let (conditions, cfg) = unfold(&parse_record(s)?, copies);
+    Ok(num_cfgs(&conditions, &cfg))
+}
+
+/// Count the arrangements of every record in the reader, one line at a time, so the full input
+/// never has to be held in memory
+pub fn solve_streaming<R: BufRead>(r: R, copies: usize) -> Result<u128> {
+    let mut total = 0;
+    for line in r.lines() {
+        let (conditions, cfg) = unfold(&parse_record(&line?)?, copies);
+        total += num_cfgs(&conditions, &cfg);
     }
-    num_cfgs
+    Ok(total)
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let file = File::open(path)?;
-    let records = BufReader::new(file)
-        .lines()
-        .map(|lr| parse_record(&lr?))
-        .collect::<Result<Vec<_>, _>>()?;
-    Ok((part_a(&records), part_b(&records).into()))
+/// Parse the puzzle input
+pub fn parse(s: &str) -> Result<Vec<Record>> {
+    s.lines().map(parse_record).collect()
+}
+
+/// Solve both parts using the parsed puzzle input
+pub fn solve(records: &[Record], part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part
+        .includes_a()
+        .then(|| usize::try_from(part_a(records)))
+        .transpose()?;
+    let b = part
+        .includes_b()
+        .then(|| usize::try_from(part_b(records)))
+        .transpose()?;
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let records = parse(input)?;
+    solve(&records, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use std::io::Cursor;
 
     test_real_input!(12, 7670, 157_383_940_585_037);
 
@@ -157,4 +197,45 @@ mod test {
     fn test_part_b() {
         assert_eq!(part_b(&example_input()), 525152);
     }
+
+    #[test]
+    fn test_unfold() {
+        let record = parse_record("???.### 1,1,3").unwrap();
+        assert_eq!(unfold(&record, 1), record);
+        assert_eq!(
+            unfold(&record, 2),
+            parse_record("???.###????.### 1,1,3,1,1,3").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_num_arrangements() {
+        assert_eq!(num_arrangements("?###???????? 3,2,1", 1).unwrap(), 10);
+        assert_eq!(num_arrangements("?###???????? 3,2,1", 5).unwrap(), 506250);
+
+        // Twelve single damaged springs spread over 65 unknown springs
+        let num_cfgs = num_arrangements("?????????? 1,1", 6).unwrap();
+        assert_eq!(num_cfgs, 343_006_888_770);
+        assert!(num_cfgs > u128::from(u32::MAX));
+    }
+
+    #[test]
+    fn test_solve_streaming() {
+        let input = concat!(
+            "???.### 1,1,3\n",
+            ".??..??...?##. 1,1,3\n",
+            "?#?#?#?#?#?#?#? 1,3,1,6\n",
+            "????.#...#... 4,1,1\n",
+            "????.######..#####. 1,6,5\n",
+            "?###???????? 3,2,1\n",
+        );
+        assert_eq!(
+            solve_streaming(Cursor::new(input), 1).unwrap(),
+            part_a(&example_input())
+        );
+        assert_eq!(
+            solve_streaming(Cursor::new(input), 5).unwrap(),
+            part_b(&example_input())
+        );
+    }
 }
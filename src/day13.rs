@@ -1,42 +1,65 @@
+use crate::Part;
 use anyhow::{anyhow, Result};
-use std::collections::HashSet;
 use std::path::Path;
 
-#[derive(Clone, PartialEq, Eq)]
-struct Note {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note {
     width: usize,
     height: usize,
-    rocks: HashSet<(usize, usize)>,
+
+    /// Every row as a bitmask where bit `x` is set if there is a rock in that column
+    rows: Vec<u128>,
+
+    /// Every column as a bitmask where bit `y` is set if there is a rock in that row
+    cols: Vec<u128>,
+}
+
+/// A line of reflection given by the number of rows above it or the number of columns to the left
+/// of it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mirror {
+    Horizontal(usize),
+    Vertical(usize),
+}
+
+impl Mirror {
+    fn summary(&self) -> usize {
+        match self {
+            Self::Horizontal(rows_above) => 100 * rows_above,
+            Self::Vertical(cols_left) => *cols_left,
+        }
+    }
 }
 
 impl Note {
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
     fn toggle_rock(&self, x: usize, y: usize) -> Self {
         let mut fixed_note = self.clone();
-        match fixed_note.rocks.take(&(x, y)) {
-            Some(_) => {}
-            None => {
-                fixed_note.rocks.insert((x, y));
-            }
-        }
+        fixed_note.rows[y] ^= 1 << x;
+        fixed_note.cols[x] ^= 1 << y;
         fixed_note
     }
-
-    fn is_rock(&self, x: usize, y: usize) -> bool {
-        self.rocks.contains(&(x, y))
-    }
 }
 
-fn parse_notes(s: &str) -> Result<Vec<Note>> {
+/// Parse the puzzle input
+pub fn parse(s: &str) -> Result<Vec<Note>> {
     let mut notes = Vec::new();
     for note_str in s.split("\n\n") {
         let mut width = 0;
         let mut height = 0;
-        let mut rocks = HashSet::new();
+        let mut rocks = Vec::new();
         for (y, line) in note_str.lines().enumerate() {
             for (x, c) in line.chars().enumerate() {
                 match c {
                     '#' => {
-                        rocks.insert((x, y));
+                        rocks.push((x, y));
                     }
                     '.' => {}
                     _ => {
@@ -47,94 +70,131 @@ fn parse_notes(s: &str) -> Result<Vec<Note>> {
             }
             height = y + 1;
         }
+
+        if width > 128 || height > 128 {
+            return Err(anyhow!(
+                "Notes may be at most 128x128, got {}x{}",
+                width,
+                height
+            ));
+        }
+        let mut rows = vec![0; height];
+        let mut cols = vec![0; width];
+        for (x, y) in rocks {
+            rows[y] |= 1 << x;
+            cols[x] |= 1 << y;
+        }
         notes.push(Note {
             width,
             height,
-            rocks,
+            rows,
+            cols,
         });
     }
     Ok(notes)
 }
 
-fn find_mirror(note: &Note, ignore: Option<usize>) -> Option<usize> {
-    // Find horizontal mirror
-    for y in 0..(note.height - 1) {
-        let num_lines_required = (y + 1).min(note.height - y - 1);
-        let mut is_mirror = true;
-        for dy in 0..num_lines_required {
-            let y_above = y - dy;
-            let y_below = y + 1 + dy;
+/// Return the width and height of every note
+pub fn note_dimensions(notes: &[Note]) -> Vec<(usize, usize)> {
+    notes.iter().map(|n| (n.width(), n.height())).collect()
+}
 
-            if (0..note.width).any(|x| note.is_rock(x, y_above) != note.is_rock(x, y_below)) {
-                is_mirror = false;
-                break;
-            }
-        }
-        let mirror = Some(100 * (y + 1));
-        if is_mirror && mirror != ignore {
-            return mirror;
-        }
+/// Count the cells that differ from their reflection across the given line. Returns `None` for
+/// lines along the edges of the note, since they can't be mirrors
+fn reflection_diff(note: &Note, line: Mirror) -> Option<usize> {
+    let (lines, at) = match line {
+        Mirror::Horizontal(y) => (&note.rows, y),
+        Mirror::Vertical(x) => (&note.cols, x),
+    };
+    if at == 0 || at >= lines.len() {
+        return None;
     }
 
-    // Find vertical mirror
-    for x in 0..(note.width - 1) {
-        let num_cols_required = (x + 1).min(note.width - x - 1);
-        let mut is_mirror = true;
-        for dx in 0..num_cols_required {
-            let x_left = x - dx;
-            let x_right = x + 1 + dx;
+    // Pair up the lines on either side of the mirror, moving outwards until one side runs out
+    let diff = lines[..at]
+        .iter()
+        .rev()
+        .zip(&lines[at..])
+        .map(|(before, after)| (before ^ after).count_ones())
+        .sum::<u32>();
+    Some(diff as usize)
+}
 
-            if (0..note.height).any(|y| note.is_rock(x_left, y) != note.is_rock(x_right, y)) {
-                is_mirror = false;
-                break;
-            }
-        }
-        let mirror = Some(x + 1);
-        if is_mirror && mirror != ignore {
-            return mirror;
-        }
-    }
-    None
+/// Return true if the note is perfectly reflected across the given line
+pub fn reflects_at(note: &Note, line: Mirror) -> bool {
+    reflection_diff(note, line) == Some(0)
+}
+
+/// Find the line of reflection where exactly the given number of cells differ from their
+/// reflection. A perfect mirror has no differences, while a mirror with a smudge has one
+pub fn find_mirror_with_diff(note: &Note, target_diff: usize) -> Option<Mirror> {
+    let horizontal = (1..note.height).map(Mirror::Horizontal);
+    let vertical = (1..note.width).map(Mirror::Vertical);
+    horizontal
+        .chain(vertical)
+        .find(|&mirror| reflection_diff(note, mirror) == Some(target_diff))
+}
+
+fn find_mirror(note: &Note, ignore: Option<Mirror>) -> Option<Mirror> {
+    let horizontal = (1..note.height).map(Mirror::Horizontal);
+    let vertical = (1..note.width).map(Mirror::Vertical);
+    horizontal
+        .chain(vertical)
+        .find(|&mirror| Some(mirror) != ignore && reflects_at(note, mirror))
 }
 
 fn part_a(notes: &[Note]) -> Result<usize> {
     let mut sum = 0;
     for note in notes {
-        sum += find_mirror(note, None).ok_or_else(|| anyhow!("No mirror found"))?;
+        sum += find_mirror_with_diff(note, 0)
+            .ok_or_else(|| anyhow!("No mirror found"))?
+            .summary();
     }
     Ok(sum)
 }
 
-fn part_b(notes: &[Note]) -> Result<usize> {
-    let mut sum_without_smudges = 0;
-    'outer: for note in notes {
-        let mirror_with_smudge =
-            find_mirror(note, None).ok_or_else(|| anyhow!("No mirror found"))?;
-
-        // Fix all possible smudges
-        for y in 0..note.height {
-            for x in 0..note.width {
-                // Fix possible smudge
-                let fixed_note = note.toggle_rock(x, y);
-                let Some(mirror_without_smudge) =
-                    find_mirror(&fixed_note, Some(mirror_with_smudge))
-                else {
-                    continue;
-                };
-
-                sum_without_smudges += mirror_without_smudge;
-                continue 'outer;
+/// Find the smudge that has to be fixed to get a new line of reflection. Returns the coordinate of
+/// the smudge along with the new line of reflection
+pub fn find_smudge(note: &Note) -> Option<((usize, usize), Mirror)> {
+    let mirror_with_smudge = find_mirror(note, None)?;
+
+    // Fix all possible smudges
+    for y in 0..note.height {
+        for x in 0..note.width {
+            let fixed_note = note.toggle_rock(x, y);
+            if let Some(mirror_without_smudge) = find_mirror(&fixed_note, Some(mirror_with_smudge))
+            {
+                return Some(((x, y), mirror_without_smudge));
             }
         }
-        return Err(anyhow!("Failed to find new mirror after fixing smudges"));
+    }
+    None
+}
+
+fn part_b(notes: &[Note]) -> Result<usize> {
+    let mut sum_without_smudges = 0;
+    for note in notes {
+        let mirror_without_smudge = find_mirror_with_diff(note, 1)
+            .ok_or_else(|| anyhow!("Failed to find new mirror after fixing smudges"))?;
+        sum_without_smudges += mirror_without_smudge.summary();
     }
     Ok(sum_without_smudges)
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let notes_str = std::fs::read_to_string(path)?;
-    let notes = parse_notes(&notes_str)?;
-    Ok((part_a(&notes)?, part_b(&notes)?.into()))
+/// Solve both parts using the parsed puzzle input
+pub fn solve(notes: &[Note], part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part.includes_a().then(|| part_a(notes)).transpose()?;
+    let b = part.includes_b().then(|| part_b(notes)).transpose()?;
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let notes = parse(input)?;
+    solve(&notes, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -144,7 +204,7 @@ mod test {
     test_real_input!(13, 41_859, 30_842);
 
     fn example_input() -> Vec<Note> {
-        parse_notes(concat!(
+        parse(concat!(
             "#.##..##.\n",
             "..#.##.#.\n",
             "##......#\n",
@@ -164,13 +224,96 @@ mod test {
         .unwrap()
     }
 
+    #[test]
+    fn test_reflects_at() {
+        let notes = example_input();
+
+        assert!(reflects_at(&notes[0], Mirror::Vertical(5)));
+        assert!(!reflects_at(&notes[0], Mirror::Vertical(4)));
+        assert!(!reflects_at(&notes[0], Mirror::Vertical(6)));
+        assert!(!reflects_at(&notes[0], Mirror::Horizontal(3)));
+
+        assert!(reflects_at(&notes[1], Mirror::Horizontal(4)));
+        assert!(!reflects_at(&notes[1], Mirror::Horizontal(3)));
+        assert!(!reflects_at(&notes[1], Mirror::Horizontal(5)));
+        assert!(!reflects_at(&notes[1], Mirror::Vertical(5)));
+
+        // Lines along the edges are never mirrors
+        assert!(!reflects_at(&notes[1], Mirror::Horizontal(0)));
+        assert!(!reflects_at(&notes[1], Mirror::Vertical(9)));
+    }
+
+    #[test]
+    fn test_wide_notes() {
+        // A note wider than 64 columns still fits in the bitmasks
+        let row = format!("{}#.{}", ".".repeat(49), ".".repeat(49));
+        let mirrored_row = format!("{}.#{}", ".".repeat(49), ".".repeat(49));
+        let notes = parse(&format!("{}\n{}\n{}\n", row, row, mirrored_row)).unwrap();
+        assert_eq!(note_dimensions(&notes), vec![(100, 3)]);
+        assert_eq!(
+            find_mirror_with_diff(&notes[0], 0),
+            Some(Mirror::Horizontal(1))
+        );
+        assert_eq!(
+            find_mirror_with_diff(&notes[0], 2),
+            Some(Mirror::Horizontal(2))
+        );
+
+        assert!(parse(&".".repeat(129)).is_err());
+    }
+
     #[test]
     fn test_part_a() {
         assert_eq!(part_a(&example_input()).unwrap(), 405);
     }
 
+    #[test]
+    fn test_find_mirror_with_diff() {
+        let notes = example_input();
+        assert_eq!(
+            find_mirror_with_diff(&notes[0], 0),
+            Some(Mirror::Vertical(5))
+        );
+        assert_eq!(
+            find_mirror_with_diff(&notes[1], 0),
+            Some(Mirror::Horizontal(4))
+        );
+        for note in &notes {
+            assert_eq!(
+                find_mirror_with_diff(note, 1),
+                find_smudge(note).map(|(_, mirror)| mirror)
+            );
+        }
+
+        // The first two columns of the first note differ in the top and bottom rows
+        assert_eq!(
+            find_mirror_with_diff(&notes[0], 2),
+            Some(Mirror::Vertical(1))
+        );
+    }
+
     #[test]
     fn test_part_b() {
         assert_eq!(part_b(&example_input()).unwrap(), 400);
     }
+
+    #[test]
+    fn test_note_dimensions() {
+        let notes = example_input();
+        assert_eq!(note_dimensions(&notes), vec![(9, 7), (9, 7)]);
+        assert_eq!(note_dimensions(&[]), Vec::new());
+    }
+
+    #[test]
+    fn test_find_smudge() {
+        let notes = example_input();
+
+        let ((x, y), mirror) = find_smudge(&notes[0]).unwrap();
+        assert_eq!(((x, y), mirror), ((0, 0), Mirror::Horizontal(3)));
+        let fixed_note = notes[0].toggle_rock(x, y);
+        assert!(reflects_at(&fixed_note, Mirror::Horizontal(3)));
+        assert!(!reflects_at(&notes[0], Mirror::Horizontal(3)));
+
+        assert_eq!(find_smudge(&notes[1]).unwrap().1, Mirror::Horizontal(1));
+    }
 }
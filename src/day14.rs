@@ -1,147 +1,203 @@
+use crate::grid::Grid;
+use crate::Part;
 use anyhow::{anyhow, Result};
-use std::collections::HashSet;
+use std::collections::HashMap;
 use std::path::Path;
 
-#[derive(Clone)]
-struct Platform {
-    width: usize,
-    height: usize,
-    round: HashSet<(usize, usize)>,
-    cube: HashSet<(usize, usize)>,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Platform {
+    grid: Grid<Cell>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Cell {
+    Empty,
+    Round,
+    Cube,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    West,
+    South,
+    East,
 }
 
 impl Platform {
+    fn num_cells(&self, cell: Cell) -> usize {
+        self.grid.iter_coords().filter(|&(_, &c)| c == cell).count()
+    }
+
+    /// Return the number of round rocks that can roll
+    pub fn num_round(&self) -> usize {
+        self.num_cells(Cell::Round)
+    }
+
+    /// Return the number of cube shaped rocks that stay in place
+    pub fn num_cube(&self) -> usize {
+        self.num_cells(Cell::Cube)
+    }
+
     fn load(&self) -> usize {
-        self.round.iter().map(|(_, y)| self.height - y).sum()
-    }
-
-    fn tilt_north(&mut self) {
-        // Start from the top
-        for y in 1..self.height {
-            for x in 0..self.width {
-                let Some(_) = self.round.take(&(x, y)) else {
-                    continue;
-                };
-                let mut ny = y;
-                while ny > 0
-                    && !self.round.contains(&(x, ny - 1))
-                    && !self.cube.contains(&(x, ny - 1))
-                {
-                    ny -= 1;
-                }
-                self.round.insert((x, ny));
+        self.load_toward(Direction::North)
+    }
+
+    /// Return the load on the support beams on the given edge of the platform
+    pub fn load_toward(&self, dir: Direction) -> usize {
+        self.grid
+            .iter_coords()
+            .filter(|&(_, &c)| c == Cell::Round)
+            .map(|((x, y), _)| match dir {
+                Direction::North => self.grid.height() - y,
+                Direction::West => self.grid.width() - x,
+                Direction::South => y + 1,
+                Direction::East => x + 1,
+            })
+            .sum()
+    }
+
+    fn is_round(&self, x: usize, y: usize) -> bool {
+        self.grid.get(x, y) == Some(&Cell::Round)
+    }
+
+    fn move_rock(&mut self, from: (usize, usize), to: (usize, usize)) {
+        if from == to {
+            return;
+        }
+        if let Some(cell) = self.grid.get_mut(from.0, from.1) {
+            *cell = Cell::Empty;
+        }
+        if let Some(cell) = self.grid.get_mut(to.0, to.1) {
+            *cell = Cell::Round;
+        }
+    }
+
+    /// Roll all round rocks on the line toward its first position. Every rock ends up in the first
+    /// free position after the closest cube shaped rock or rolled rock in front of it
+    fn roll_line(&mut self, line: impl Iterator<Item = (usize, usize)>) {
+        let line = line.collect::<Vec<_>>();
+        let mut free = 0;
+        for (i, &(x, y)) in line.iter().enumerate() {
+            if self.is_round(x, y) {
+                self.move_rock((x, y), line[free]);
+                free += 1;
+            } else if self.grid.get(x, y) == Some(&Cell::Cube) {
+                free = i + 1;
             }
         }
     }
 
-    fn tilt_cycle(&mut self) {
-        self.tilt_north();
-
-        // West
-        for x in 1..self.width {
-            for y in 0..self.height {
-                let Some(_) = self.round.take(&(x, y)) else {
-                    continue;
-                };
-                let mut nx = x;
-                while nx > 0
-                    && !self.round.contains(&(nx - 1, y))
-                    && !self.cube.contains(&(nx - 1, y))
-                {
-                    nx -= 1;
+    /// Roll all round rocks as far as possible in the given direction
+    pub fn tilt(&mut self, dir: Direction) {
+        let (width, height) = (self.grid.width(), self.grid.height());
+        match dir {
+            Direction::North => {
+                for x in 0..width {
+                    self.roll_line((0..height).map(|y| (x, y)));
                 }
-                self.round.insert((nx, y));
             }
-        }
-        // South
-        for y in (0..self.height - 1).rev() {
-            for x in 0..self.width {
-                let Some(_) = self.round.take(&(x, y)) else {
-                    continue;
-                };
-                let mut ny = y;
-                while ny + 1 < self.height
-                    && !self.round.contains(&(x, ny + 1))
-                    && !self.cube.contains(&(x, ny + 1))
-                {
-                    ny += 1;
+            Direction::West => {
+                for y in 0..height {
+                    self.roll_line((0..width).map(|x| (x, y)));
                 }
-                self.round.insert((x, ny));
             }
-        }
-        // East
-        for x in (0..self.width - 1).rev() {
-            for y in 0..self.height {
-                let Some(_) = self.round.take(&(x, y)) else {
-                    continue;
-                };
-                let mut nx = x;
-                while nx + 1 < self.width
-                    && !self.round.contains(&(nx + 1, y))
-                    && !self.cube.contains(&(nx + 1, y))
-                {
-                    nx += 1;
+            Direction::South => {
+                for x in 0..width {
+                    self.roll_line((0..height).rev().map(|y| (x, y)));
+                }
+            }
+            Direction::East => {
+                for y in 0..height {
+                    self.roll_line((0..width).rev().map(|x| (x, y)));
                 }
-                self.round.insert((nx, y));
             }
         }
     }
-}
 
-fn parse(s: &str) -> Result<Platform> {
-    let mut round = HashSet::new();
-    let mut cube = HashSet::new();
-    let mut width = 0;
-    let mut height = 0;
-    for (y, line) in s.lines().enumerate() {
-        for (x, c) in line.chars().enumerate() {
-            match c {
-                'O' => {
-                    round.insert((x, y));
-                }
-                '#' => {
-                    cube.insert((x, y));
-                }
-                '.' => {}
-                _ => {
-                    return Err(anyhow!("HM"));
-                }
-            }
-            width = x + 1;
+    /// Return a bitset with one bit per cell in row major order that is set for round rocks. Cube
+    /// shaped rocks never move, so this identifies the state of the platform
+    fn round_bits(&self) -> Vec<u64> {
+        let mut bits = vec![0; (self.grid.width() * self.grid.height()).div_ceil(64)];
+        for ((x, y), _) in self.grid.iter_coords().filter(|&(_, &c)| c == Cell::Round) {
+            let i = y * self.grid.width() + x;
+            bits[i / 64] |= 1 << (i % 64);
         }
-        height = y + 1;
-    }
-    Ok(Platform {
-        width,
-        height,
-        round,
-        cube,
-    })
+        bits
+    }
+
+    fn tilt_cycle(&mut self) {
+        for dir in [
+            Direction::North,
+            Direction::West,
+            Direction::South,
+            Direction::East,
+        ] {
+            self.tilt(dir);
+        }
+    }
+}
+
+/// Return a copy of the platform with all round rocks rolled as far as possible in the given
+/// direction
+pub fn tilted(platform: &Platform, dir: Direction) -> Platform {
+    let mut platform = platform.clone();
+    platform.tilt(dir);
+    platform
+}
+
+/// Parse the puzzle input
+pub fn parse(s: &str) -> Result<Platform> {
+    let grid = Grid::from_str_with(s, |c| match c {
+        'O' => Ok(Cell::Round),
+        '#' => Ok(Cell::Cube),
+        '.' => Ok(Cell::Empty),
+        _ => Err(anyhow!("Invalid tile {:?}", c)),
+    })?;
+    Ok(Platform { grid })
 }
 
 fn part_a(mut platform: Platform) -> usize {
-    platform.tilt_north();
+    platform.tilt(Direction::North);
     platform.load()
 }
 
-fn part_b(mut platform: Platform) -> usize {
-    let mut history = Vec::new();
-    let offset = loop {
+/// Spin the platform until it ends up in a state it has been in before. Returns how many spin
+/// cycles it takes until the states start repeating, how many spin cycles it takes to get back to
+/// the same state, and the load after every spin cycle until then
+fn find_cycle(mut platform: Platform) -> (usize, usize, Vec<usize>) {
+    let mut seen = HashMap::new();
+    let mut loads = Vec::new();
+    loop {
         platform.tilt_cycle();
-        if let Some(i) = history.iter().position(|r| r == &platform.round) {
-            break i;
+        let round_bits = platform.round_bits();
+        if let Some(&offset) = seen.get(&round_bits) {
+            return (offset, loads.len() - offset, loads);
         }
-        history.push(platform.round.clone());
-    };
-    let i = offset + (1_000_000_000 - offset - 1) % (history.len() - offset);
-    platform.round = history[i].clone();
-    platform.load()
+        seen.insert(round_bits, loads.len());
+        loads.push(platform.load());
+    }
+}
+
+fn part_b(platform: Platform) -> usize {
+    let (offset, period, loads) = find_cycle(platform);
+    loads[offset + (1_000_000_000 - offset - 1) % period]
+}
+
+/// Solve both parts using the parsed puzzle input
+pub fn solve(platform: &Platform, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part.includes_a().then(|| part_a(platform.clone()));
+    let b = part.includes_b().then(|| part_b(platform.clone()));
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let platform = parse(input)?;
+    solve(&platform, part)
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let input = std::fs::read_to_string(path)?;
-    let platform = parse(&input)?;
-    Ok((part_a(platform.clone()), part_b(platform.clone()).into()))
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -150,7 +206,7 @@ mod test {
 
     test_real_input!(14, 108918, 100310);
 
-    const EXAMPLE_INPUT: &'static str = concat!(
+    const EXAMPLE_INPUT: &str = concat!(
         "OOOO.#.O..\n",
         "OO..#....#\n",
         "OO..O##..O\n",
@@ -163,7 +219,7 @@ mod test {
         "#....#....\n",
     );
 
-    const EXAMPLE_INPUT_1_CYCLE: &'static str = concat!(
+    const EXAMPLE_INPUT_1_CYCLE: &str = concat!(
         ".....#....\n",
         "....#...O#\n",
         "...OO##...\n",
@@ -176,7 +232,7 @@ mod test {
         "#..OO#....\n",
     );
 
-    const EXAMPLE_INPUT_2_CYCLE: &'static str = concat!(
+    const EXAMPLE_INPUT_2_CYCLE: &str = concat!(
         ".....#....\n",
         "....#...O#\n",
         ".....##...\n",
@@ -189,7 +245,7 @@ mod test {
         "#.OOO#...O\n",
     );
 
-    const EXAMPLE_INPUT_3_CYCLE: &'static str = concat!(
+    const EXAMPLE_INPUT_3_CYCLE: &str = concat!(
         ".....#....\n",
         "....#...O#\n",
         ".....##...\n",
@@ -202,20 +258,137 @@ mod test {
         "#.OOO#...O\n",
     );
 
+    #[test]
+    fn test_tilted() {
+        #[rustfmt::skip]
+        let platform = parse(concat!(
+            "O.#.\n",
+            ".O..\n",
+            "O..O\n",
+            "#O.O\n",
+        ))
+        .unwrap();
+
+        #[rustfmt::skip]
+        let north = parse(concat!(
+            "OO#O\n",
+            "OO.O\n",
+            "....\n",
+            "#...\n",
+        ))
+        .unwrap();
+        assert_eq!(tilted(&platform, Direction::North), north);
+
+        #[rustfmt::skip]
+        let east = parse(concat!(
+            ".O#.\n",
+            "...O\n",
+            "..OO\n",
+            "#.OO\n",
+        ))
+        .unwrap();
+        assert_eq!(tilted(&platform, Direction::East), east);
+
+        // The original platform is left untouched
+        assert_eq!(platform.num_round(), 6);
+        assert!(platform.is_round(1, 1));
+    }
+
+    #[test]
+    fn test_load_toward() {
+        let platform = parse(EXAMPLE_INPUT).unwrap();
+        assert_eq!(platform.load(), 136);
+        assert_eq!(platform.load_toward(Direction::North), platform.load());
+        assert_eq!(platform.load_toward(Direction::South), 62);
+
+        // Every rock contributes height + 1 to the sum of the north and south loads
+        assert_eq!(
+            platform.load_toward(Direction::North) + platform.load_toward(Direction::South),
+            platform.num_round() * (platform.grid.height() + 1)
+        );
+    }
+
+    #[test]
+    fn test_rock_counts() {
+        let mut platform = parse(EXAMPLE_INPUT).unwrap();
+        assert_eq!(platform.num_round(), 18);
+        assert_eq!(platform.num_cube(), 17);
+
+        // Tilting moves rocks around but never adds or removes any
+        for _ in 0..3 {
+            platform.tilt_cycle();
+            assert_eq!(platform.num_round(), 18);
+            assert_eq!(platform.num_cube(), 17);
+        }
+    }
+
+    #[test]
+    fn test_round_bits() {
+        let platform = parse("O.#\n.O.\n").unwrap();
+        assert_eq!(platform.round_bits(), vec![0b10001]);
+
+        // Cube shaped rocks never move, so round rocks alone tell platforms apart
+        let a = parse(EXAMPLE_INPUT).unwrap();
+        let b = tilted(&a, Direction::West);
+        assert_ne!(a.round_bits(), b.round_bits());
+        assert_eq!(b.round_bits(), tilted(&b, Direction::West).round_bits());
+    }
+
+    #[test]
+    fn test_tilt_sequence() {
+        // Tilting in the opposite order ends up in a different state than a regular spin cycle
+        let mut spun = parse(EXAMPLE_INPUT).unwrap();
+        spun.tilt_cycle();
+        let mut reversed = parse(EXAMPLE_INPUT).unwrap();
+        for dir in [
+            Direction::East,
+            Direction::South,
+            Direction::West,
+            Direction::North,
+        ] {
+            reversed.tilt(dir);
+        }
+        assert_ne!(spun, reversed);
+        assert_eq!(reversed, tilted(&reversed, Direction::North));
+        assert_eq!(reversed.num_round(), spun.num_round());
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        // Compare against scanning every previous platform for a repetition
+        let mut platform = parse(EXAMPLE_INPUT).unwrap();
+        let mut history = Vec::new();
+        let offset = loop {
+            platform.tilt_cycle();
+            if let Some(i) = history.iter().position(|p| p == &platform) {
+                break i;
+            }
+            history.push(platform.clone());
+        };
+
+        let (found_offset, period, loads) = find_cycle(parse(EXAMPLE_INPUT).unwrap());
+        assert_eq!((found_offset, period), (offset, history.len() - offset));
+        assert_eq!(
+            loads,
+            history.iter().map(Platform::load).collect::<Vec<_>>()
+        );
+        assert_eq!(part_b(parse(EXAMPLE_INPUT).unwrap()), 64);
+    }
+
     #[test]
     fn test_tilt_cycle() {
         let mut start = parse(EXAMPLE_INPUT).unwrap();
 
         let after_1_cycle = parse(EXAMPLE_INPUT_1_CYCLE).unwrap();
         start.tilt_cycle();
-        assert_eq!(start.round, after_1_cycle.round);
+        assert_eq!(start, after_1_cycle);
 
         let after_2_cycle = parse(EXAMPLE_INPUT_2_CYCLE).unwrap();
         start.tilt_cycle();
-        assert_eq!(start.round, after_2_cycle.round);
+        assert_eq!(start, after_2_cycle);
 
         let after_3_cycle = parse(EXAMPLE_INPUT_3_CYCLE).unwrap();
         start.tilt_cycle();
-        assert_eq!(start.round, after_3_cycle.round);
+        assert_eq!(start, after_3_cycle);
     }
 }
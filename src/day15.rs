@@ -1,3 +1,4 @@
+use crate::Part;
 use anyhow::{anyhow, Result};
 use std::collections::HashMap;
 use std::path::Path;
@@ -43,10 +44,28 @@ fn part_b<'a>(lenses: impl Iterator<Item = &'a str>) -> Result<usize> {
     Ok(focusing_power)
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let input = std::fs::read_to_string(path)?;
-    let lenses = input.trim().split(',');
-    Ok((part_a(lenses.clone()), part_b(lenses)?.into()))
+/// Parse the puzzle input
+pub fn parse(input: &str) -> Result<Vec<&str>> {
+    Ok(input.trim().split(',').collect())
+}
+
+/// Solve both parts using the parsed puzzle input
+pub fn solve(lenses: &[&str], part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part.includes_a().then(|| part_a(lenses.iter().copied()));
+    let b = part
+        .includes_b()
+        .then(|| part_b(lenses.iter().copied()))
+        .transpose()?;
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let lenses = parse(input)?;
+    solve(&lenses, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -55,7 +74,7 @@ mod test {
 
     test_real_input!(15, 516_070, 244_981);
 
-    const EXAMPLE_INPUT: &'static str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+    const EXAMPLE_INPUT: &str = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
 
     #[test]
     fn test_hash() {
@@ -1,18 +1,20 @@
+use crate::grid::Grid;
+use crate::Part;
 use anyhow::{anyhow, Result};
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 type Coordinate = (usize, usize);
 
-struct Map {
-    width: usize,
-    height: usize,
-    mirrors: HashMap<Coordinate, Mirror>,
+#[derive(Debug)]
+pub struct Map {
+    tiles: Grid<Option<Mirror>>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Dir {
+pub enum Dir {
     Up,
     Down,
     Left,
@@ -20,12 +22,13 @@ enum Dir {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Beam {
+pub struct Beam {
     x: usize,
     y: usize,
     dir: Dir,
 }
 
+#[derive(Debug)]
 enum Mirror {
     SplitUpDown,
     SplitLeftRight,
@@ -33,13 +36,24 @@ enum Mirror {
     ReflectSlash,
 }
 
+/// Results of following beams between splitters, shared by all beams traced over the same map
+#[derive(Debug, Default)]
+struct BeamCache {
+    /// For every beam entering a splitter, the tiles lit until the beams leaving the splitter
+    /// enter other splitters, along with the beams entering those splitters
+    splits: HashMap<Beam, (Vec<Coordinate>, Vec<Beam>)>,
+
+    /// For every beam entering a splitter, a bitset of all tiles it eventually illuminates
+    illuminated: HashMap<Beam, Vec<u64>>,
+}
+
 enum MaybePair<T> {
     Pair(T, T),
     Single(T), // T_T
 }
 
 impl Beam {
-    fn new(x: usize, y: usize, dir: Dir) -> Self {
+    pub fn new(x: usize, y: usize, dir: Dir) -> Self {
         Self { x, y, dir }
     }
 
@@ -86,16 +100,25 @@ impl Map {
     fn advance_beam(&self, beam: &Beam) -> Option<Beam> {
         let (x, y) = match beam.dir {
             Dir::Up => (beam.x, beam.y.checked_sub(1)?),
-            Dir::Down => (beam.x, (beam.y + 1 < self.height).then_some(beam.y + 1)?),
+            Dir::Down => (
+                beam.x,
+                (beam.y + 1 < self.tiles.height()).then_some(beam.y + 1)?,
+            ),
             Dir::Left => (beam.x.checked_sub(1)?, beam.y),
-            Dir::Right => ((beam.x + 1 < self.width).then_some(beam.x + 1)?, beam.y),
+            Dir::Right => (
+                (beam.x + 1 < self.tiles.width()).then_some(beam.x + 1)?,
+                beam.y,
+            ),
         };
         Some(Beam { x, y, ..*beam })
     }
 
-    fn num_illuminated_tiles(&self, seed_beam: Beam) -> usize {
+    /// Follow the seed beam until it has no more new tiles to visit. Returns all beam states
+    /// visited as well as the number of times a beam was split in two
+    fn trace(&self, seed_beam: Beam) -> (HashSet<Beam>, usize) {
         let mut beams = vec![seed_beam];
         let mut visited = HashSet::new();
+        let mut num_splits = 0;
         while let Some(beam) = beams.pop() {
             // If we have already reached this tile from this angle we skip searching it
             if !visited.insert(beam) {
@@ -103,8 +126,9 @@ impl Map {
             }
 
             let next_beam = self
-                .mirrors
-                .get(&(beam.x, beam.y))
+                .tiles
+                .get(beam.x, beam.y)
+                .and_then(Option::as_ref)
                 // We hit a mirror and need to consider the reflection
                 .map(|mirror| {
                     mirror
@@ -113,6 +137,9 @@ impl Map {
                 })
                 // We didn't hit a mirror and need to advance
                 .unwrap_or_else(|| MaybePair::Single(self.advance_beam(&beam)));
+            if let MaybePair::Pair(_, _) = next_beam {
+                num_splits += 1;
+            }
             match next_beam {
                 MaybePair::Pair(Some(a), Some(b)) => {
                     beams.push(a);
@@ -126,47 +153,250 @@ impl Map {
                 MaybePair::Pair(None, None) | MaybePair::Single(None) => {}
             }
         }
+        (visited, num_splits)
+    }
+
+    fn num_illuminated_tiles(&self, seed_beam: Beam) -> usize {
+        let (visited, _) = self.trace(seed_beam);
 
         // We shouldn't double count the same tile visited from different angles
         let unique_tiles: HashSet<_> = visited.into_iter().map(|b| (b.x, b.y)).collect();
         unique_tiles.len()
     }
+
+    /// Follow a beam through empty tiles and reflecting mirrors until it enters a splitter or
+    /// leaves the map. Returns the tiles lit on the way, not including the splitter, along with
+    /// the beam entering the splitter
+    fn follow(&self, mut beam: Beam) -> (Vec<Coordinate>, Option<Beam>) {
+        // Without splitters every beam state can only be reached from a single previous state, so
+        // a beam that starts at an edge or a splitter never ends up in a loop
+        let mut tiles = Vec::new();
+        loop {
+            let dir = match self.tiles.get(beam.x, beam.y).and_then(Option::as_ref) {
+                Some(Mirror::SplitUpDown | Mirror::SplitLeftRight) => return (tiles, Some(beam)),
+                Some(mirror) => {
+                    let MaybePair::Single(dir) = mirror.reflect(beam.dir) else {
+                        unreachable!("Only splitters split beams");
+                    };
+                    dir
+                }
+                None => beam.dir,
+            };
+            tiles.push((beam.x, beam.y));
+            match self.advance_beam(&beam.turn(dir)) {
+                Some(next_beam) => beam = next_beam,
+                None => return (tiles, None),
+            }
+        }
+    }
+
+    /// Return the tiles lit by a beam entering a splitter until it reaches the next splitters,
+    /// along with the beams entering those splitters
+    fn split(&self, beam: Beam) -> (Vec<Coordinate>, Vec<Beam>) {
+        let dirs = match self.tiles.get(beam.x, beam.y).and_then(Option::as_ref) {
+            Some(mirror) => match mirror.reflect(beam.dir) {
+                MaybePair::Pair(a, b) => vec![a, b],
+                MaybePair::Single(d) => vec![d],
+            },
+            None => vec![beam.dir],
+        };
+
+        let mut tiles = vec![(beam.x, beam.y)];
+        let mut splitters = Vec::new();
+        for dir in dirs {
+            if let Some(next_beam) = self.advance_beam(&beam.turn(dir)) {
+                let (lit, splitter) = self.follow(next_beam);
+                tiles.extend(lit);
+                splitters.extend(splitter);
+            }
+        }
+        (tiles, splitters)
+    }
+
+    fn set_tile(&self, lit: &mut [u64], (x, y): Coordinate) {
+        let i = y * self.tiles.width() + x;
+        lit[i / 64] |= 1 << (i % 64);
+    }
+
+    /// Return a bitset of every tile illuminated by a beam entering a splitter. Both the paths
+    /// between splitters and the final result are cached, so later beams reaching the same
+    /// splitter reuse them
+    fn illuminated_from_splitter(&self, beam: Beam, cache: &mut BeamCache) -> Vec<u64> {
+        if let Some(lit) = cache.illuminated.get(&beam) {
+            return lit.clone();
+        }
+
+        let mut lit = vec![0; (self.tiles.width() * self.tiles.height()).div_ceil(64)];
+        let mut visited = HashSet::from([beam]);
+        let mut to_visit = vec![beam];
+        while let Some(splitter) = to_visit.pop() {
+            let (tiles, next_splitters) = cache
+                .splits
+                .entry(splitter)
+                .or_insert_with(|| self.split(splitter));
+            for &tile in tiles.iter() {
+                self.set_tile(&mut lit, tile);
+            }
+            for &next_splitter in next_splitters.iter() {
+                if visited.insert(next_splitter) {
+                    to_visit.push(next_splitter);
+                }
+            }
+        }
+        cache.illuminated.insert(beam, lit.clone());
+        lit
+    }
+
+    /// Count the tiles illuminated by the seed beam like `num_illuminated_tiles`, reusing what
+    /// earlier beams found out about the splitters it reaches
+    fn num_illuminated_tiles_cached(&self, seed_beam: Beam, cache: &mut BeamCache) -> usize {
+        let (tiles, splitter) = self.follow(seed_beam);
+        let mut lit = match splitter {
+            Some(splitter) => self.illuminated_from_splitter(splitter, cache),
+            None => vec![0; (self.tiles.width() * self.tiles.height()).div_ceil(64)],
+        };
+        for tile in tiles {
+            self.set_tile(&mut lit, tile);
+        }
+        lit.iter().map(|bits| bits.count_ones() as usize).sum()
+    }
+
+    /// Return the directions in which a beam from the given seed entered each illuminated tile
+    pub fn direction_map(&self, seed_beam: Beam) -> HashMap<Coordinate, HashSet<Dir>> {
+        let mut directions: HashMap<_, HashSet<_>> = HashMap::new();
+        for beam in self.trace(seed_beam).0 {
+            directions
+                .entry((beam.x, beam.y))
+                .or_default()
+                .insert(beam.dir);
+        }
+        directions
+    }
+
+    /// Return all beams entering the map from one of its edges
+    fn edge_beams(&self) -> impl Iterator<Item = Beam> + '_ {
+        let (width, height) = (self.tiles.width(), self.tiles.height());
+        let from_top = (0..width).map(|x| Beam::new(x, 0, Dir::Down));
+        let from_bottom = (0..width).map(move |x| Beam::new(x, height - 1, Dir::Up));
+        let from_left = (0..height).map(|y| Beam::new(0, y, Dir::Right));
+        let from_right = (0..height).map(move |y| Beam::new(width - 1, y, Dir::Left));
+        from_top
+            .chain(from_bottom)
+            .chain(from_left)
+            .chain(from_right)
+    }
+
+    /// Return every beam entering the map from one of its edges along with the number of tiles it
+    /// illuminates
+    pub fn illumination_distribution(&self) -> Vec<(Beam, usize)> {
+        let seed_beams = self.edge_beams().collect::<Vec<_>>();
+        let illuminate = |cache: &mut BeamCache, seed_beam| {
+            (
+                seed_beam,
+                self.num_illuminated_tiles_cached(seed_beam, cache),
+            )
+        };
+
+        #[cfg(feature = "rayon")]
+        let distribution = {
+            use rayon::prelude::*;
+            seed_beams
+                .into_par_iter()
+                .map_init(BeamCache::default, illuminate)
+                .collect()
+        };
+        #[cfg(not(feature = "rayon"))]
+        let distribution = {
+            let mut cache = BeamCache::default();
+            seed_beams
+                .into_iter()
+                .map(|seed_beam| illuminate(&mut cache, seed_beam))
+                .collect()
+        };
+
+        distribution
+    }
+
+    /// Return the highest number of illuminated tiles for any beam entering from the edge, along
+    /// with every edge beam that reaches that number
+    pub fn best_entries(&self) -> (usize, Vec<Beam>) {
+        let mut max_illuminated = 0;
+        let mut best_beams = Vec::new();
+        for (seed_beam, num_illuminated) in self.illumination_distribution() {
+            if num_illuminated > max_illuminated {
+                max_illuminated = num_illuminated;
+                best_beams.clear();
+            }
+            if num_illuminated == max_illuminated {
+                best_beams.push(seed_beam);
+            }
+        }
+        (max_illuminated, best_beams)
+    }
+
+    /// Return the highest number of illuminated tiles for any beam entering from the edge. Stops as
+    /// soon as a beam illuminates every tile, since no other beam can beat that
+    pub fn max_illuminated_tiles(&self) -> usize {
+        self.search_max_illuminated_tiles().0
+    }
+
+    /// Same as `max_illuminated_tiles`, but also returns how many edge beams were traced before
+    /// the search stopped
+    fn search_max_illuminated_tiles(&self) -> (usize, usize) {
+        let num_tiles = self.tiles.width() * self.tiles.height();
+        let seed_beams = self.edge_beams().collect::<Vec<_>>();
+        let num_traced = AtomicUsize::new(0);
+
+        // A fully illuminated map is reported as an error to stop the search early
+        let illuminate = |cache: &mut BeamCache, seed_beam| {
+            num_traced.fetch_add(1, Ordering::Relaxed);
+            match self.num_illuminated_tiles_cached(seed_beam, cache) {
+                n if n == num_tiles => Err(n),
+                n => Ok(n),
+            }
+        };
+
+        #[cfg(feature = "rayon")]
+        let max_illuminated = {
+            use rayon::prelude::*;
+            seed_beams
+                .into_par_iter()
+                .map_init(BeamCache::default, illuminate)
+                .try_reduce(|| 0, |a, b| Ok(a.max(b)))
+        };
+        #[cfg(not(feature = "rayon"))]
+        let max_illuminated = {
+            let mut cache = BeamCache::default();
+            seed_beams
+                .into_iter()
+                .map(|seed_beam| illuminate(&mut cache, seed_beam))
+                .try_fold(0, |acc, n| Ok(acc.max(n?)))
+        };
+
+        match max_illuminated {
+            Ok(n) | Err(n) => (n, num_traced.into_inner()),
+        }
+    }
+
+    /// Return the number of times a beam from the given seed hits a splitter from the side
+    pub fn num_split_events(&self, seed_beam: Beam) -> usize {
+        self.trace(seed_beam).1
+    }
 }
 
 impl FromStr for Map {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let mut width = 0;
-        let mut height = 0;
-        let mut mirrors = HashMap::new();
-        for (y, line) in s.lines().enumerate() {
-            for (x, c) in line.chars().enumerate() {
-                match c {
-                    '/' => {
-                        mirrors.insert((x, y), Mirror::ReflectSlash);
-                    }
-                    '\\' => {
-                        mirrors.insert((x, y), Mirror::ReflectBackslash);
-                    }
-                    '|' => {
-                        mirrors.insert((x, y), Mirror::SplitUpDown);
-                    }
-                    '-' => {
-                        mirrors.insert((x, y), Mirror::SplitLeftRight);
-                    }
-                    '.' => {}
-                    _ => return Err(anyhow!("Unknown tile {:?}", c)),
-                }
-                width = x + 1
-            }
-            height = y + 1
-        }
-        Ok(Self {
-            width,
-            height,
-            mirrors,
-        })
+        let tiles = Grid::from_str_with(s, |c| match c {
+            '/' => Ok(Some(Mirror::ReflectSlash)),
+            '\\' => Ok(Some(Mirror::ReflectBackslash)),
+            '|' => Ok(Some(Mirror::SplitUpDown)),
+            '-' => Ok(Some(Mirror::SplitLeftRight)),
+            '.' => Ok(None),
+            _ => Err(anyhow!("Unknown tile {:?}", c)),
+        })?;
+        Ok(Self { tiles })
     }
 }
 
@@ -175,23 +405,28 @@ fn part_a(map: &Map) -> usize {
 }
 
 fn part_b(map: &Map) -> usize {
-    let from_top = (0..map.width).map(|x| Beam::new(x, 0, Dir::Down));
-    let from_bottom = (0..map.width).map(|x| Beam::new(x, map.height - 1, Dir::Up));
-    let from_left = (0..map.height).map(|y| Beam::new(0, y, Dir::Right));
-    let from_right = (0..map.height).map(|y| Beam::new(map.width - 1, y, Dir::Left));
-    from_top
-        .chain(from_bottom)
-        .chain(from_left)
-        .chain(from_right)
-        .map(|seed_beam| map.num_illuminated_tiles(seed_beam))
-        .max()
-        .unwrap()
+    map.max_illuminated_tiles()
+}
+
+/// Parse the puzzle input
+pub fn parse(input: &str) -> Result<Map> {
+    input.parse()
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let map_str = std::fs::read_to_string(path)?;
-    let map = map_str.parse()?;
-    Ok((part_a(&map), part_b(&map).into()))
+/// Solve both parts using the parsed puzzle input
+pub fn solve(map: &Map, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part.includes_a().then(|| part_a(map));
+    let b = part.includes_b().then(|| part_b(map));
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let map = parse(input)?;
+    solve(&map, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -200,7 +435,7 @@ mod test {
 
     test_real_input!(16, 8539, 8674);
 
-    const EXAMPLE_INPUT: &'static str = concat!(
+    const EXAMPLE_INPUT: &str = concat!(
         ".|...\\....\n",
         "|.-.\\.....\n",
         ".....|-...\n",
@@ -218,8 +453,108 @@ mod test {
         assert_eq!(part_a(&EXAMPLE_INPUT.parse().unwrap()), 46);
     }
 
+    #[test]
+    fn test_num_split_events() {
+        let map: Map = EXAMPLE_INPUT.parse().unwrap();
+        assert_eq!(map.num_split_events(Beam::new(0, 0, Dir::Right)), 9);
+    }
+
+    #[test]
+    fn test_direction_map() {
+        let map: Map = EXAMPLE_INPUT.parse().unwrap();
+        let directions = map.direction_map(Beam::new(0, 0, Dir::Right));
+        assert_eq!(directions.len(), 46);
+
+        // The splitter in the top row is hit both from the left and from the right
+        assert_eq!(directions[&(1, 0)], HashSet::from([Dir::Left, Dir::Right]));
+        assert_eq!(directions[&(0, 0)], HashSet::from([Dir::Right]));
+    }
+
     #[test]
     fn test_part_b() {
         assert_eq!(part_b(&EXAMPLE_INPUT.parse().unwrap()), 51);
     }
+
+    #[test]
+    fn test_full_illumination() {
+        // The beam entering at the top left snakes through every tile
+        #[rustfmt::skip]
+        let map: Map = concat!(
+            "..\\\n",
+            "/./\n",
+            "\\..\n",
+        )
+        .parse()
+        .unwrap();
+        assert_eq!(map.num_illuminated_tiles(Beam::new(0, 0, Dir::Right)), 9);
+        assert_eq!(part_b(&map), 9);
+
+        // The search stops at the first beam entering from the left, after the ones entering from
+        // the top and bottom
+        let (max_illuminated, num_traced) = map.search_max_illuminated_tiles();
+        assert_eq!(max_illuminated, 9);
+        assert!(num_traced <= map.edge_beams().count());
+        #[cfg(not(feature = "rayon"))]
+        assert_eq!(num_traced, 7);
+
+        // Every beam lights up the whole column, so the first one is enough
+        let map: Map = ".\n.\n.\n".parse().unwrap();
+        assert_eq!(part_b(&map), 3);
+        assert_eq!(map.best_entries().0, 3);
+        #[cfg(not(feature = "rayon"))]
+        assert_eq!(map.search_max_illuminated_tiles(), (3, 1));
+
+        // No beam lights up an open square, so every one of them is traced
+        let map: Map = "...\n...\n...\n".parse().unwrap();
+        assert_eq!(map.search_max_illuminated_tiles(), (3, 12));
+    }
+
+    #[test]
+    fn test_cached_illumination() {
+        let map: Map = EXAMPLE_INPUT.parse().unwrap();
+        let mut cache = BeamCache::default();
+        for seed_beam in map.edge_beams() {
+            assert_eq!(
+                map.num_illuminated_tiles_cached(seed_beam, &mut cache),
+                map.num_illuminated_tiles(seed_beam)
+            );
+        }
+        assert!(!cache.illuminated.is_empty());
+
+        // Beams stuck in a loop between splitters are only traced once
+        #[rustfmt::skip]
+        let map: Map = concat!(
+            ".-..\\\n",
+            ".....\n",
+            ".\\.-/\n",
+        )
+        .parse()
+        .unwrap();
+        let mut cache = BeamCache::default();
+        for seed_beam in map.edge_beams() {
+            assert_eq!(
+                map.num_illuminated_tiles_cached(seed_beam, &mut cache),
+                map.num_illuminated_tiles(seed_beam)
+            );
+        }
+    }
+
+    #[test]
+    fn test_illumination_distribution() {
+        let map: Map = EXAMPLE_INPUT.parse().unwrap();
+        let distribution = map.illumination_distribution();
+        assert_eq!(distribution.len(), map.edge_beams().count());
+        assert_eq!(distribution.iter().map(|(_, n)| *n).max(), Some(51));
+    }
+
+    #[test]
+    fn test_best_entries() {
+        let map: Map = EXAMPLE_INPUT.parse().unwrap();
+        let (max_illuminated, best_beams) = map.best_entries();
+        assert_eq!(max_illuminated, 51);
+        assert!(!best_beams.is_empty());
+        for beam in best_beams {
+            assert_eq!(map.num_illuminated_tiles(beam), 51);
+        }
+    }
 }
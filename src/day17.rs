@@ -1,135 +1,103 @@
+use crate::direction::Direction;
+use crate::search::a_star;
+use crate::Part;
 use anyhow::{anyhow, Result};
-use std::cmp::Reverse;
-use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::collections::HashMap;
 use std::path::Path;
 use std::str::FromStr;
 
 type Coordinate = (usize, usize);
 
-struct Map {
+#[derive(Debug)]
+pub struct Map {
     width: usize,
     height: usize,
     blocks: HashMap<Coordinate, usize>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Ord, Eq, Hash)]
-enum Dir {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct Moves {
-    estimated_cost: usize,
-    current_cost: usize,
-    x: usize,
-    y: usize,
-    dir: Dir,
-    num_straight_moves: usize,
-}
-
 fn manhattan_distance((ax, ay): Coordinate, (bx, by): Coordinate) -> usize {
     ax.abs_diff(bx) + ay.abs_diff(by)
 }
 
-impl Dir {
-    fn turn_left(&self) -> Self {
-        match self {
-            Dir::Up => Dir::Left,
-            Dir::Down => Dir::Right,
-            Dir::Left => Dir::Down,
-            Dir::Right => Dir::Up,
-        }
+impl Map {
+    fn cheapest_path(&self, min_straight_moves: usize, max_straight_moves: usize) -> Option<usize> {
+        self.cheapest_path_from(
+            min_straight_moves,
+            max_straight_moves,
+            &[Direction::Right, Direction::Down],
+        )
     }
 
-    fn turn_right(&self) -> Self {
-        match self {
-            Dir::Up => Dir::Right,
-            Dir::Down => Dir::Left,
-            Dir::Left => Dir::Up,
-            Dir::Right => Dir::Down,
-        }
+    /// Find the cheapest path like `cheapest_path`, and also return the number of states that were
+    /// expanded during the search
+    pub fn cheapest_path_stats(
+        &self,
+        min_straight_moves: usize,
+        max_straight_moves: usize,
+    ) -> Option<(usize, usize)> {
+        let mut num_expanded = 0;
+        let cost = self.search(
+            min_straight_moves,
+            max_straight_moves,
+            &[Direction::Right, Direction::Down],
+            &mut num_expanded,
+        )?;
+        Some((cost, num_expanded))
     }
-}
 
-impl Map {
-    fn cheapest_path(&self, min_straight_moves: usize, max_straight_moves: usize) -> Option<usize> {
+    /// Find the cheapest path where the crucible may only start moving in one of the given
+    /// directions
+    pub fn cheapest_path_from(
+        &self,
+        min_straight_moves: usize,
+        max_straight_moves: usize,
+        start_dirs: &[Direction],
+    ) -> Option<usize> {
+        self.search(min_straight_moves, max_straight_moves, start_dirs, &mut 0)
+    }
+
+    fn search(
+        &self,
+        min_straight_moves: usize,
+        max_straight_moves: usize,
+        start_dirs: &[Direction],
+        num_expanded: &mut usize,
+    ) -> Option<usize> {
         let source = (0, 0);
         let target = (self.width - 1, self.height - 1);
 
-        let mut to_visit = BinaryHeap::new();
-        let mut visited = HashSet::new();
-
-        to_visit.push(Reverse(Moves {
-            estimated_cost: manhattan_distance(source, target),
-            current_cost: 0,
-            x: source.0,
-            y: source.1,
-            dir: Dir::Right,
-            num_straight_moves: 0,
-        }));
-        visited.insert((source.0, source.1, Dir::Right, 0));
-        to_visit.push(Reverse(Moves {
-            estimated_cost: manhattan_distance(source, target),
-            current_cost: 0,
-            x: source.0,
-            y: source.1,
-            dir: Dir::Down,
-            num_straight_moves: 0,
-        }));
-        visited.insert((source.0, source.1, Dir::Down, 0));
-
-        while let Some(Reverse(mov)) = to_visit.pop() {
-            if (mov.x, mov.y) == target {
-                return Some(mov.current_cost);
-            }
-
-            for dir in [mov.dir, mov.dir.turn_left(), mov.dir.turn_right()] {
-                let num_straight_moves = if dir == mov.dir {
-                    mov.num_straight_moves + 1
-                } else {
-                    1
-                };
+        // Each node is a position, the direction we're moving in and how many steps we've taken
+        // in that direction
+        let starts = start_dirs.iter().map(|&dir| (source.0, source.1, dir, 0));
+        a_star(
+            starts,
+            |&(x, y, _, _)| (x, y) == target,
+            |&(x, y, dir, num_straight_moves)| {
+                *num_expanded += 1;
+                [dir, dir.turn_left(), dir.turn_right()]
+                    .into_iter()
+                    .filter_map(move |new_dir| {
+                        let new_num_straight_moves = if new_dir == dir {
+                            num_straight_moves + 1
+                        } else {
+                            1
+                        };
 
-                if num_straight_moves > max_straight_moves {
-                    continue;
-                }
-
-                if dir != mov.dir && mov.num_straight_moves < min_straight_moves {
-                    continue;
-                }
+                        if new_num_straight_moves > max_straight_moves {
+                            return None;
+                        }
 
-                let Some((x, y)) = (match dir {
-                    Dir::Up => mov.y.checked_sub(1).map(|y| (mov.x, y)),
-                    Dir::Down => Some((mov.x, mov.y + 1)),
-                    Dir::Left => mov.x.checked_sub(1).map(|x| (x, mov.y)),
-                    Dir::Right => Some((mov.x + 1, mov.y)),
-                }) else {
-                    continue;
-                };
-
-                let Some(cost) = self.blocks.get(&(x, y)) else {
-                    continue;
-                };
-
-                if !visited.insert((x, y, dir, num_straight_moves)) {
-                    continue;
-                }
+                        if new_dir != dir && num_straight_moves < min_straight_moves {
+                            return None;
+                        }
 
-                let new_move = Moves {
-                    estimated_cost: mov.current_cost + cost + manhattan_distance((x, y), target),
-                    current_cost: mov.current_cost + cost,
-                    x,
-                    y,
-                    dir,
-                    num_straight_moves,
-                };
-                to_visit.push(Reverse(new_move));
-            }
-        }
-        None
+                        let (nx, ny) = new_dir.checked_step((x, y))?;
+                        let cost = self.blocks.get(&(nx, ny))?;
+                        Some(((nx, ny, new_dir, new_num_straight_moves), *cost))
+                    })
+            },
+            |&(x, y, _, _)| manhattan_distance((x, y), target),
+        )
     }
 }
 
@@ -167,19 +135,35 @@ fn part_b(map: &Map) -> usize {
     map.cheapest_path(4, 10).unwrap()
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let map_str = std::fs::read_to_string(path)?;
-    let map = map_str.parse()?;
-    Ok((part_a(&map), part_b(&map).into()))
+/// Parse the puzzle input
+pub fn parse(input: &str) -> Result<Map> {
+    input.parse()
+}
+
+/// Solve both parts using the parsed puzzle input
+pub fn solve(map: &Map, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part.includes_a().then(|| part_a(map));
+    let b = part.includes_b().then(|| part_b(map));
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let map = parse(input)?;
+    solve(&map, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use std::ops::Range;
 
     test_real_input!(17, 1256, 1382);
 
-    const EXAMPLE_INPUT: &'static str = concat!(
+    const EXAMPLE_INPUT: &str = concat!(
         "2413432311323\n",
         "3215453535623\n",
         "3255245654254\n",
@@ -195,11 +179,81 @@ mod test {
         "4322674655533\n",
     );
 
+    /// Check that allowing longer straight runs never makes the cheapest path more expensive
+    fn assert_monotonic_in_max(map: &Map, min_straight_moves: usize, max_range: Range<usize>) {
+        let costs = max_range
+            .map(|max| map.cheapest_path(min_straight_moves, max))
+            .collect::<Vec<_>>();
+        for pair in costs.windows(2) {
+            if let Some(tighter) = pair[0] {
+                let wider = pair[1].expect("Wider constraints must still find a path");
+                assert!(
+                    wider <= tighter,
+                    "{:?} for min {}",
+                    costs,
+                    min_straight_moves
+                );
+            }
+        }
+    }
+
+    /// Generate a square map of random costs using a linear congruential generator
+    fn random_map(size: usize, seed: u64) -> Map {
+        let mut state = seed;
+        let mut s = String::new();
+        for _ in 0..size {
+            for _ in 0..size {
+                state = state
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1);
+                s.push(char::from(b'1' + ((state >> 33) % 9) as u8));
+            }
+            s.push('\n');
+        }
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_monotonic_in_max() {
+        let example: Map = EXAMPLE_INPUT.parse().unwrap();
+        for (min, max) in [(1, 1), (1, 3), (2, 5), (4, 4), (4, 10)] {
+            assert_monotonic_in_max(&example, min, max..max + 4);
+        }
+
+        for seed in 0..5 {
+            let map = random_map(4 + seed as usize, seed);
+            for min in [1, 2, 3] {
+                assert_monotonic_in_max(&map, min, min..min + 6);
+            }
+        }
+    }
+
     #[test]
     fn test_part_a() {
         assert_eq!(part_a(&EXAMPLE_INPUT.parse().unwrap()), 102);
     }
 
+    #[test]
+    fn test_start_dirs() {
+        let map: Map = EXAMPLE_INPUT.parse().unwrap();
+        let both = map.cheapest_path(1, 3).unwrap();
+        assert!(map.cheapest_path_from(1, 3, &[Direction::Right]).unwrap() >= both);
+        assert!(map.cheapest_path_from(1, 3, &[Direction::Down]).unwrap() >= both);
+        assert_eq!(map.cheapest_path_from(1, 3, &[]), None);
+    }
+
+    #[test]
+    fn test_cheapest_path_stats() {
+        let map: Map = EXAMPLE_INPUT.parse().unwrap();
+        let (cost, num_expanded) = map.cheapest_path_stats(1, 3).unwrap();
+        assert_eq!(cost, 102);
+        assert_eq!(Some(cost), map.cheapest_path(1, 3));
+        assert!(num_expanded > 0);
+
+        let (cost, _) = map.cheapest_path_stats(4, 10).unwrap();
+        assert_eq!(Some(cost), map.cheapest_path(4, 10));
+    }
+
     #[test]
     fn test_part_b() {
         assert_eq!(part_b(&EXAMPLE_INPUT.parse().unwrap()), 94);
@@ -1,4 +1,8 @@
+use crate::direction::Direction;
+use crate::grid::flood_fill;
+use crate::Part;
 use anyhow::{anyhow, Result};
+use std::collections::HashSet;
 use std::path::Path;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -7,31 +11,23 @@ struct Coordinate {
     y: isize,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum Dir {
-    Up,
-    Down,
-    Left,
-    Right,
-}
+/// Largest bounding box area we're willing to enumerate cell by cell
+const MAX_ENUMERABLE_AREA: usize = 1_000_000;
 
 impl Coordinate {
     fn new(x: isize, y: isize) -> Self {
         Self { x, y }
     }
 
-    fn advance(self, dir: Dir, steps: isize) -> Self {
-        match dir {
-            Dir::Up => Self::new(self.x, self.y - steps),
-            Dir::Down => Self::new(self.x, self.y + steps),
-            Dir::Left => Self::new(self.x - steps, self.y),
-            Dir::Right => Self::new(self.x + steps, self.y),
-        }
+    fn advance(self, dir: Direction, steps: isize) -> Self {
+        let (dx, dy) = dir.step((0, 0));
+        Self::new(self.x + dx * steps, self.y + dy * steps)
     }
 }
 
+/// Parse the puzzle input
 #[allow(clippy::type_complexity)]
-fn parse_instructions(s: &str) -> Result<(Vec<(Dir, usize)>, Vec<(Dir, usize)>)> {
+pub fn parse(s: &str) -> Result<(Vec<(Direction, usize)>, Vec<(Direction, usize)>)> {
     let mut a = Vec::new();
     let mut b = Vec::new();
     for line in s.lines() {
@@ -43,48 +39,69 @@ fn parse_instructions(s: &str) -> Result<(Vec<(Dir, usize)>, Vec<(Dir, usize)>)>
             return Err(anyhow!("Invalid instruction {:?}", line));
         };
 
-        let a_dir = match dir_str {
-            "U" => Dir::Up,
-            "D" => Dir::Down,
-            "L" => Dir::Left,
-            "R" => Dir::Right,
-            _ => return Err(anyhow!("Unexpected direction {:?}", dir_str)),
-        };
+        let a_dir = dir_str
+            .parse()
+            .ok()
+            .and_then(|c| Direction::from_letter(c).ok())
+            .ok_or_else(|| anyhow!("Unexpected direction {:?}", dir_str))?;
         a.push((a_dir, num_str.parse()?));
 
         // B
-        let Some(color_str) = rest
-            .strip_prefix("(#")
-            .and_then(|s| s.strip_suffix(')'))
-            .filter(|s| s.len() == 6)
-        else {
-            return Err(anyhow!("Invalid color {:?}", rest));
-        };
-        let color = usize::from_str_radix(color_str, 16)?;
-        let b_dir = match color & 0xf {
-            0 => Dir::Right,
-            1 => Dir::Down,
-            2 => Dir::Left,
-            3 => Dir::Up,
-            _ => return Err(anyhow!("Invalid direction in color string {:?}", color_str)),
-        };
-        b.push((b_dir, color >> 4));
+        b.push(parse_color(rest)?);
     }
     Ok((a, b))
 }
 
-fn trench_area(dig_instructions: &[(Dir, usize)]) -> usize {
-    // We start digging at 0x0 to simplify the calculation
-    let mut curr = Coordinate::new(0, 0);
-    let mut trench_corners = vec![curr];
+/// Parse a color field like `(#70c710)` into the instruction it encodes
+fn parse_color(s: &str) -> Result<(Direction, usize)> {
+    let Some(color_str) = s
+        .strip_prefix("(#")
+        .and_then(|s| s.strip_suffix(')'))
+        .filter(|s| s.len() == 6)
+    else {
+        return Err(anyhow!("Invalid color {:?}", s));
+    };
+    let color = usize::from_str_radix(color_str, 16)?;
+    let dir = match color & 0xf {
+        0 => Direction::Right,
+        1 => Direction::Down,
+        2 => Direction::Left,
+        3 => Direction::Up,
+        _ => return Err(anyhow!("Invalid direction in color string {:?}", color_str)),
+    };
+    Ok((dir, color >> 4))
+}
 
-    // Find all corners of the trench as well as the number of cells on the edge
-    let mut edge = 0;
+/// Parse only the instructions encoded in the color field of every line, which is all part B
+/// needs
+pub fn parse_hex_instructions(s: &str) -> Result<Vec<(Direction, usize)>> {
+    s.lines()
+        .map(|line| {
+            let Some((_, color_str)) = line.rsplit_once(' ') else {
+                return Err(anyhow!("Invalid instruction {:?}", line));
+            };
+            parse_color(color_str)
+        })
+        .collect()
+}
+
+/// Return all corners of the trench in digging order, starting at 0x0
+fn trench_corners(dig_instructions: &[(Direction, usize)]) -> Vec<Coordinate> {
+    let mut curr = Coordinate::new(0, 0);
+    let mut corners = vec![curr];
     for (dir, num) in dig_instructions {
         curr = curr.advance(*dir, *num as isize);
-        trench_corners.push(curr);
-        edge += *num;
+        corners.push(curr);
     }
+    corners
+}
+
+fn trench_area(dig_instructions: &[(Direction, usize)]) -> usize {
+    // We start digging at 0x0 to simplify the calculation
+    let trench_corners = trench_corners(dig_instructions);
+
+    // The number of cells on the edge
+    let edge = dig_instructions.iter().map(|(_, num)| num).sum::<usize>();
 
     // Use a modified version of the shoelace formula (Gauss formula) to calculate the area. I
     // think this is not covering half of the edge cells which is why we need to add them at the
@@ -103,13 +120,69 @@ fn trench_area(dig_instructions: &[(Dir, usize)]) -> usize {
     (sum.unsigned_abs() + edge) / 2 + 1
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let instructions_str = std::fs::read_to_string(path)?;
-    let (a_instructions, b_instructions) = parse_instructions(&instructions_str)?;
-    Ok((
-        trench_area(&a_instructions),
-        trench_area(&b_instructions).into(),
-    ))
+/// Return every cell dug out while following the instructions, starting at 0x0
+fn trench_cells(dig_instructions: &[(Direction, usize)]) -> HashSet<(isize, isize)> {
+    let mut curr = Coordinate::new(0, 0);
+    let mut cells = HashSet::new();
+    cells.insert((curr.x, curr.y));
+    for (dir, num) in dig_instructions {
+        for _ in 0..*num {
+            curr = curr.advance(*dir, 1);
+            cells.insert((curr.x, curr.y));
+        }
+    }
+    cells
+}
+
+/// Return all cells enclosed by the trench, not including the trench itself. Returns `None` if the
+/// trench is too large for the cells to be enumerated
+pub fn interior_cells(dig_instructions: &[(Direction, usize)]) -> Option<HashSet<(isize, isize)>> {
+    // The corners span the same bounding box as the cells, so check the size before digging out
+    // every cell. Add a margin of one cell around the trench so the outside is connected
+    let corners = trench_corners(dig_instructions);
+    let min_x = corners.iter().map(|c| c.x).min()? - 1;
+    let max_x = corners.iter().map(|c| c.x).max()? + 1;
+    let min_y = corners.iter().map(|c| c.y).min()? - 1;
+    let max_y = corners.iter().map(|c| c.y).max()? + 1;
+    let area = (max_x - min_x + 1).unsigned_abs() * (max_y - min_y + 1).unsigned_abs();
+    if area > MAX_ENUMERABLE_AREA {
+        return None;
+    }
+    let trench = trench_cells(dig_instructions);
+
+    // Flood fill the outside starting from a corner that we know is outside the trench
+    let outside = flood_fill(
+        (min_x, min_y),
+        Some(((min_x, min_y), (max_x, max_y))),
+        |c| !trench.contains(&c),
+    );
+
+    Some(
+        (min_y..=max_y)
+            .flat_map(|y| (min_x..=max_x).map(move |x| (x, y)))
+            .filter(|c| !trench.contains(c) && !outside.contains(c))
+            .collect(),
+    )
+}
+
+/// Solve both parts using the parsed puzzle input
+#[allow(clippy::type_complexity)]
+pub fn solve(
+    (a_instructions, b_instructions): &(Vec<(Direction, usize)>, Vec<(Direction, usize)>),
+    part: Part,
+) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part.includes_a().then(|| trench_area(a_instructions));
+    let b = part.includes_b().then(|| trench_area(b_instructions));
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let parsed = parse(input)?;
+    solve(&parsed, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -118,7 +191,7 @@ mod test {
 
     test_real_input!(18, 58_550, 47_452_118_468_566);
 
-    const EXAMPLE_INPUT: &'static str = concat!(
+    const EXAMPLE_INPUT: &str = concat!(
         "R 6 (#70c710)\n",
         "D 5 (#0dc571)\n",
         "L 2 (#5713f0)\n",
@@ -137,16 +210,41 @@ mod test {
 
     #[test]
     fn test_part_a() {
-        assert_eq!(
-            trench_area(&parse_instructions(EXAMPLE_INPUT).unwrap().0),
-            62
-        );
+        assert_eq!(trench_area(&parse(EXAMPLE_INPUT).unwrap().0), 62);
+    }
+
+    #[test]
+    fn test_interior_cells() {
+        let (instructions, large_instructions) = parse(EXAMPLE_INPUT).unwrap();
+        let trench = trench_cells(&instructions);
+        let interior = interior_cells(&instructions).unwrap();
+        assert_eq!(trench.len(), 38);
+        assert_eq!(interior.len() + trench.len(), 62);
+        assert!(interior.is_disjoint(&trench));
+
+        assert_eq!(interior_cells(&large_instructions), None);
+    }
+
+    #[test]
+    fn test_parse_hex_instructions() {
+        let hex_instructions = parse_hex_instructions(EXAMPLE_INPUT).unwrap();
+        assert_eq!(hex_instructions, parse(EXAMPLE_INPUT).unwrap().1);
+        assert_eq!(hex_instructions[0], (Direction::Right, 461937));
+        assert!(parse_hex_instructions("R 6 (#70c71)\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_invalid_direction() {
+        for line in ["UR 6 (#70c710)\n", "X 6 (#70c710)\n"] {
+            let err = parse(line).unwrap_err().to_string();
+            assert!(err.starts_with("Unexpected direction"), "{}", err);
+        }
     }
 
     #[test]
     fn test_part_b() {
         assert_eq!(
-            trench_area(&parse_instructions(EXAMPLE_INPUT).unwrap().1),
+            trench_area(&parse(EXAMPLE_INPUT).unwrap().1),
             952_408_144_115
         );
     }
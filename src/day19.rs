@@ -4,7 +4,7 @@ use std::path::Path;
 use std::str::FromStr;
 
 #[derive(Debug, Clone)]
-struct Workflow {
+pub struct Workflow {
     name: String,
     rules: Vec<Rule>,
 }
@@ -37,13 +37,20 @@ enum Target {
 }
 
 #[derive(Debug, Clone, Copy)]
-struct Part {
+pub struct Part {
     x: usize,
     m: usize,
     a: usize,
     s: usize,
 }
 
+impl Part {
+    /// Return the sum of all ratings of the part
+    pub fn total(&self) -> usize {
+        self.x + self.m + self.a + self.s
+    }
+}
+
 impl Cmp {
     fn is_ok(&self, value: usize) -> bool {
         match self {
@@ -157,7 +164,8 @@ impl FromStr for Part {
     }
 }
 
-fn parse_input(s: &str) -> Result<(HashMap<String, Workflow>, Vec<Part>)> {
+/// Parse the puzzle input
+pub fn parse(s: &str) -> Result<(HashMap<String, Workflow>, Vec<Part>)> {
     let mut workflows = HashMap::new();
     let mut parts = Vec::new();
 
@@ -177,16 +185,24 @@ fn parse_input(s: &str) -> Result<(HashMap<String, Workflow>, Vec<Part>)> {
     Ok((workflows, parts))
 }
 
-fn part_a(workflows: &HashMap<String, Workflow>, parts: &[Part]) -> Result<usize> {
-    /// Recursively follow the branching rules and determine if the given part is accepted
-    fn is_ok(
-        workflows: &HashMap<String, Workflow>,
-        workflow_name: &str,
-        part: &Part,
-    ) -> Result<bool> {
+/// Follow the branching rules starting at the "in" workflow and determine if the given part is
+/// accepted. This is done iteratively so long chains of workflows can't overflow the stack
+fn is_accepted(workflows: &HashMap<String, Workflow>, part: &Part) -> Result<bool> {
+    Ok(trace(workflows, part)?.0)
+}
+
+/// Determine if the given part is accepted like `is_accepted`, and also return the names of the
+/// workflows it passed through in the order they were visited
+pub fn trace(workflows: &HashMap<String, Workflow>, part: &Part) -> Result<(bool, Vec<String>)> {
+    let mut visited = Vec::new();
+    let mut workflow_name = "in";
+    for _ in 0..=workflows.len() {
         let Some(workflow) = workflows.get(workflow_name) else {
             return Err(anyhow!("Missing workflow {:?}", workflow_name));
         };
+        visited.push(workflow_name.to_string());
+
+        let mut next_workflow_name = None;
         for rule in workflow.rules.iter() {
             let target = match rule {
                 Rule::Cmp(selector, cmp, target) => {
@@ -203,22 +219,64 @@ fn part_a(workflows: &HashMap<String, Workflow>, parts: &[Part]) -> Result<usize
             };
 
             match target {
-                Some(Target::SwitchWorkflow(wn)) => return is_ok(workflows, wn, part),
-                Some(Target::Accept) => return Ok(true),
-                Some(Target::Reject) => return Ok(false),
+                Some(Target::SwitchWorkflow(wn)) => {
+                    next_workflow_name = Some(wn);
+                    break;
+                }
+                Some(Target::Accept) => return Ok((true, visited)),
+                Some(Target::Reject) => return Ok((false, visited)),
                 None => (),
             }
         }
-        Err(anyhow!(
-            "Exhausted all rules for workflow {:?}",
-            workflow_name
-        ))
+
+        let Some(wn) = next_workflow_name else {
+            return Err(anyhow!(
+                "Exhausted all rules for workflow {:?}",
+                workflow_name
+            ));
+        };
+        workflow_name = wn;
+    }
+
+    // We can't visit more workflows than there are without visiting one of them twice
+    Err(anyhow!("Part {:?} is stuck in a cycle of workflows", part))
+}
+
+/// Return the names of all workflows that can't be reached from the "in" workflow, no matter which
+/// ratings a part has
+pub fn unreachable_workflows(workflows: &HashMap<String, Workflow>) -> HashSet<String> {
+    let mut visited = HashSet::new();
+    let mut to_visit = vec!["in"];
+    while let Some(workflow_name) = to_visit.pop() {
+        if !visited.insert(workflow_name) {
+            continue;
+        }
+
+        // Missing workflows are reported when solving, so we only care about the ones we have
+        let Some(workflow) = workflows.get(workflow_name) else {
+            continue;
+        };
+
+        for rule in workflow.rules.iter() {
+            let (Rule::Cmp(_, _, target) | Rule::Target(target)) = rule;
+            if let Target::SwitchWorkflow(wn) = target {
+                to_visit.push(wn);
+            }
+        }
     }
 
+    workflows
+        .keys()
+        .filter(|name| !visited.contains(name.as_str()))
+        .cloned()
+        .collect()
+}
+
+fn part_a(workflows: &HashMap<String, Workflow>, parts: &[Part]) -> Result<usize> {
     let mut sum = 0;
     for part in parts {
-        if is_ok(workflows, "in", part)? {
-            sum += part.x + part.m + part.a + part.s;
+        if is_accepted(workflows, part)? {
+            sum += part.total();
         }
     }
     Ok(sum)
@@ -286,10 +344,26 @@ fn part_b(workflows: &HashMap<String, Workflow>) -> Result<usize> {
     Ok(sum)
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let instructions_str = std::fs::read_to_string(path)?;
-    let (workflows, parts) = parse_input(&instructions_str)?;
-    Ok((part_a(&workflows, &parts)?, part_b(&workflows)?.into()))
+/// Solve both parts using the parsed puzzle input
+pub fn solve(
+    (workflows, parts): &(HashMap<String, Workflow>, Vec<Part>),
+    part: crate::Part,
+) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part
+        .includes_a()
+        .then(|| part_a(workflows, parts))
+        .transpose()?;
+    let b = part.includes_b().then(|| part_b(workflows)).transpose()?;
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: crate::Part) -> Result<(Option<usize>, Option<usize>)> {
+    let parsed = parse(input)?;
+    solve(&parsed, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, crate::Part::Both)
 }
 
 #[cfg(test)]
@@ -298,7 +372,7 @@ mod test {
 
     test_real_input!(19, 397_643, 132_392_981_697_081);
 
-    const EXAMPLE_INPUT: &'static str = concat!(
+    const EXAMPLE_INPUT: &str = concat!(
         "px{a<2006:qkq,m>2090:A,rfg}\n",
         "pv{a>1716:R,A}\n",
         "lnx{m>1548:A,A}\n",
@@ -320,13 +394,62 @@ mod test {
 
     #[test]
     fn test_part_a() {
-        let (workflows, parts) = parse_input(EXAMPLE_INPUT).unwrap();
+        let (workflows, parts) = parse(EXAMPLE_INPUT).unwrap();
         assert_eq!(part_a(&workflows, &parts).unwrap(), 19_114);
     }
 
+    #[test]
+    fn test_trace() {
+        let (workflows, parts) = parse(EXAMPLE_INPUT).unwrap();
+        let (accepted, path) = trace(&workflows, &parts[0]).unwrap();
+        assert!(accepted);
+        assert_eq!(path, vec!["in", "qqz", "qs", "lnx"]);
+
+        let (accepted, path) = trace(&workflows, &parts[1]).unwrap();
+        assert!(!accepted);
+        assert_eq!(path, vec!["in", "px", "rfg", "gd"]);
+    }
+
+    #[test]
+    fn test_part_total() {
+        let part: Part = "{x=1,m=2,a=3,s=4}".parse().unwrap();
+        assert_eq!(part.total(), 10);
+    }
+
+    #[test]
+    fn test_long_workflow_chain() {
+        let mut input = "in{w1}\n".to_string();
+        for i in 1..5000 {
+            input.push_str(&format!("w{}{{w{}}}\n", i, i + 1));
+        }
+        input.push_str("w5000{A}\n\n{x=1,m=2,a=3,s=4}\n");
+
+        let (workflows, parts) = parse(&input).unwrap();
+        assert_eq!(part_a(&workflows, &parts).unwrap(), 10);
+    }
+
+    #[test]
+    fn test_workflow_cycle() {
+        let (workflows, parts) = parse("in{a}\na{in}\n\n{x=1,m=2,a=3,s=4}\n").unwrap();
+        assert!(is_accepted(&workflows, &parts[0]).is_err());
+    }
+
+    #[test]
+    fn test_unreachable_workflows() {
+        let (workflows, _) = parse(EXAMPLE_INPUT).unwrap();
+        assert!(unreachable_workflows(&workflows).is_empty());
+
+        let input = "in{x>10:a,R}\na{A}\norphan{m<5:a,A}\n\n{x=1,m=2,a=3,s=4}\n";
+        let (workflows, _) = parse(input).unwrap();
+        assert_eq!(
+            unreachable_workflows(&workflows),
+            HashSet::from(["orphan".to_string()])
+        );
+    }
+
     #[test]
     fn test_part_b() {
-        let (workflows, _) = parse_input(EXAMPLE_INPUT).unwrap();
+        let (workflows, _) = parse(EXAMPLE_INPUT).unwrap();
         assert_eq!(part_b(&workflows).unwrap(), 167_409_079_868_000);
     }
 }
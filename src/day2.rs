@@ -1,11 +1,10 @@
+use crate::Part;
 use anyhow::{anyhow, Result};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 use std::path::Path;
 use std::str::FromStr;
 
 #[derive(Debug, PartialEq, Eq)]
-struct Game {
+pub struct Game {
     id: usize,
     rounds: Vec<Round>,
 }
@@ -25,13 +24,11 @@ impl FromStr for Game {
             .split_once(": ")
             .ok_or_else(|| anyhow!("Unable to find game ID"))?;
 
-        if !id_str.starts_with("Game ") {
-            return Err(anyhow!("Game doesn't start with 'Game '"));
-        }
-
-        let id: usize = id_str[5..]
+        // Use the trailing number as ID no matter which word precedes it
+        let prefix_len = id_str.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+        let id: usize = id_str[prefix_len..]
             .parse()
-            .map_err(|_| anyhow!("Unable to parse game ID {:?}", &id_str[5..]))?;
+            .map_err(|_| anyhow!("Unable to find game ID in {:?}", id_str))?;
         let rounds = rounds_str
             .split("; ")
             .map(Round::from_str)
@@ -113,14 +110,25 @@ fn part_b(games: &[Game]) -> usize {
     cube_power_sum
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let file = File::open(path)?;
-    let games = BufReader::new(file)
-        .lines()
-        .map(|l| l?.parse::<Game>())
-        .collect::<Result<Vec<_>, anyhow::Error>>()?;
+/// Parse the puzzle input
+pub fn parse(s: &str) -> Result<Vec<Game>> {
+    s.lines().map(|l| l.parse()).collect()
+}
+
+/// Solve both parts using the parsed puzzle input
+pub fn solve(games: &[Game], part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part.includes_a().then(|| part_a(games));
+    let b = part.includes_b().then(|| part_b(games));
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let games = parse(input)?;
+    solve(&games, part)
+}
 
-    Ok((part_a(&games), part_b(&games).into()))
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -129,7 +137,7 @@ mod test {
 
     test_real_input!(2, 2776, 68638);
 
-    const EXAMPLE_INPUT: &'static [&'static str] = &[
+    const EXAMPLE_INPUT: &[&str] = &[
         "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
         "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
         "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
@@ -200,6 +208,19 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_parsing_id_prefix() {
+        assert_eq!(
+            "Round 7: 1 red".parse::<Game>().unwrap(),
+            Game {
+                id: 7,
+                rounds: vec![Round { r: 1, g: 0, b: 0 }]
+            }
+        );
+        assert!("Game: 1 red".parse::<Game>().is_err());
+        assert!("Game 1a: 1 red".parse::<Game>().is_err());
+    }
+
     #[test]
     fn test_part_a() {
         assert_eq!(part_a(&example_input()), 8);
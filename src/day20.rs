@@ -1,27 +1,29 @@
+use crate::math::lcm;
+use crate::Part;
 use anyhow::{anyhow, Result};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::Path;
 use std::str::FromStr;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Config(HashMap<String, Module>);
+pub struct Config(HashMap<String, Module>);
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum Module {
+pub enum Module {
     FlipFlop(FlipFlop),
     Conjunction(Conjunction),
     Broadcast(Vec<String>),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct FlipFlop {
+pub struct FlipFlop {
     input_name: String,
     outputs: Vec<String>,
     is_on: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Conjunction {
+pub struct Conjunction {
     input_name: String,
     outputs: Vec<String>,
     input_is_high: HashMap<String, bool>,
@@ -73,6 +75,41 @@ impl<'a> Iterator for SignalIterator<'a> {
 }
 
 impl Config {
+    /// Iterate over all modules in the network along with their names
+    pub fn modules(&self) -> impl Iterator<Item = (&str, &Module)> {
+        self.0.iter().map(|(name, m)| (name.as_str(), m))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Module> {
+        self.0.get(name)
+    }
+
+    /// Describe the state of every module, one per line and ordered by module name. Flip-flops
+    /// show whether they're on and conjunctions show the last pulse they got from each input
+    pub fn snapshot(&self) -> String {
+        let mut names = self.0.keys().collect::<Vec<_>>();
+        names.sort();
+
+        let mut lines = Vec::new();
+        for name in names {
+            let state = match &self.0[name] {
+                Module::FlipFlop(f) => if f.is_on { "on" } else { "off" }.to_string(),
+                Module::Conjunction(c) => {
+                    let mut inputs = c.input_is_high.iter().collect::<Vec<_>>();
+                    inputs.sort();
+                    inputs
+                        .into_iter()
+                        .map(|(input, &h)| format!("{}={}", input, if h { "high" } else { "low" }))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                }
+                Module::Broadcast(_) => continue,
+            };
+            lines.push(format!("{}: {}", name, state));
+        }
+        lines.join("\n")
+    }
+
     fn iter_signals_from_button_press(&mut self) -> SignalIterator<'_> {
         let signals = [("button".to_string(), false, "broadcaster".to_string())]
             .into_iter()
@@ -200,45 +237,142 @@ fn part_a(cfg: &Config) -> usize {
     num_low * num_high
 }
 
-fn part_b(cfg: &Config) -> usize {
-    // This solution doesn't fill me with joy. It is very dependent on the particular way the input
-    // is laid out. The input basically splits the broadcast signal into 4 "counters" that wrap at
-    // different number of button pressed. The RV node basically only switches when all 4 counters
-    // wrap during the same button press cycle. The counter wrap outputs are conjunctions with more
-    // than 2 inputs, so we look for those, find out when they wrap and use that to calculate when
-    // "rv" would eventually trigger. We can just multiply each cycle time together as they are all
-    // prime.
+/// Press the button once and return every signal that was sent, in the order they were sent. Each
+/// signal is its source module, whether the pulse was high and its destination module
+pub fn press_log(cfg: &mut Config) -> Vec<(String, bool, String)> {
+    cfg.iter_signals_from_button_press().collect()
+}
+
+/// Return the first button press during which the given module emits a high pulse. Returns None
+/// if the network gets back to a state it has already been in without that happening
+pub fn first_high_press(cfg: &Config, module: &str) -> Option<usize> {
+    cfg.get(module)?;
+
     let mut state = cfg.clone();
+    let mut seen_states = HashSet::new();
+    for num_presses in 1usize.. {
+        if !seen_states.insert(state.snapshot()) {
+            return None;
+        }
+        let mut emitted_high = false;
+        for (s, h, _) in state.iter_signals_from_button_press() {
+            emitted_high |= h && s == module;
+        }
+        if emitted_high {
+            return Some(num_presses);
+        }
+    }
+    None
+}
 
-    // Find counters
-    let mut cycle_times: HashMap<String, Option<usize>> = HashMap::new();
-    for m in cfg.0.values() {
-        if let Module::Conjunction(c) = m {
-            if c.outputs.len() > 2 {
-                cycle_times.insert(c.input_name.clone(), None);
+/// Find the conjunctions that feed the conjunction in front of `rx`. Each one sits at the end of
+/// a counter and emits a high pulse when its counter wraps
+fn counter_modules(cfg: &Config) -> Vec<String> {
+    let inputs_of = |name: &str| {
+        cfg.0
+            .values()
+            .filter(move |m| m.outputs().iter().any(|o| o == name))
+            .collect::<Vec<_>>()
+    };
+
+    // We expect exactly one conjunction to feed rx
+    let [Module::Conjunction(rx_feeder)] = inputs_of("rx")[..] else {
+        return Vec::new();
+    };
+
+    let mut counters = inputs_of(&rx_feeder.input_name)
+        .into_iter()
+        .filter_map(|m| match m {
+            Module::Conjunction(c) => Some(c.input_name.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    counters.sort();
+    counters
+}
+
+/// Follow each output of the broadcaster through the chain of flip-flops it feeds, until the chain
+/// ends in a conjunction. Each chain is the bit sequence of one of the counters part B relies on
+pub fn broadcaster_branches(cfg: &Config) -> Vec<Vec<String>> {
+    let Some(Module::Broadcast(outputs)) = cfg.get("broadcaster") else {
+        return Vec::new();
+    };
+
+    let mut branches = Vec::new();
+    for output in outputs {
+        let mut branch: Vec<String> = Vec::new();
+        let mut next = Some(output);
+        while let Some(name) = next.take() {
+            let Some(Module::FlipFlop(flip_flop)) = cfg.get(name) else {
+                break;
+            };
+            if branch.contains(name) {
+                break;
             }
+            branch.push(name.clone());
+            next = flip_flop
+                .outputs
+                .iter()
+                .find(|o| matches!(cfg.get(o), Some(Module::FlipFlop(_))));
         }
+        branches.push(branch);
+    }
+    branches
+}
+
+fn part_b(cfg: &Config) -> Result<usize> {
+    // This solution doesn't fill me with joy. It is very dependent on the particular way the input
+    // is laid out. The input basically splits the broadcast signal into a number of "counters" that
+    // wrap at different number of button pressed. The conjunction in front of rx only emits a low
+    // pulse when all counters wrap during the same button press cycle. Each counter wrap is
+    // signaled by a high pulse from one of the conjunctions feeding it, so we find out when they
+    // wrap and use that to calculate when "rx" would eventually trigger.
+    let mut state = cfg.clone();
+
+    // Find counters
+    let mut cycle_times: HashMap<String, Option<usize>> = counter_modules(cfg)
+        .into_iter()
+        .map(|name| (name, None))
+        .collect();
+    if cycle_times.is_empty() {
+        return Err(anyhow!("Unable to find any counters feeding rx"));
     }
 
     for num_presses in 1usize.. {
         for (s, h, _) in state.iter_signals_from_button_press() {
             for (counter_name, cycle_time) in cycle_times.iter_mut() {
-                if &s == counter_name && !h {
+                if &s == counter_name && h && cycle_time.is_none() {
                     *cycle_time = Some(num_presses);
                 }
             }
         }
         if cycle_times.values().all(Option::is_some) {
-            return cycle_times.into_values().flatten().product();
+            return Ok(cycle_times.into_values().flatten().fold(1, lcm));
         }
     }
     // We can't get here for a very long time
     unreachable!();
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let cfg: Config = std::fs::read_to_string(path)?.parse()?;
-    Ok((part_a(&cfg), part_b(&cfg).into()))
+/// Parse the puzzle input
+pub fn parse(input: &str) -> Result<Config> {
+    input.parse()
+}
+
+/// Solve both parts using the parsed puzzle input
+pub fn solve(cfg: &Config, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part.includes_a().then(|| part_a(cfg));
+    let b = part.includes_b().then(|| part_b(cfg)).transpose()?;
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let cfg = parse(input)?;
+    solve(&cfg, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -247,7 +381,7 @@ mod test {
 
     test_real_input!(20, 737_679_780, 227_411_378_431_763);
 
-    const EXAMPLE_INPUT_1: &'static str = concat!(
+    const EXAMPLE_INPUT_1: &str = concat!(
         "broadcaster -> a, b, c\n",
         "%a -> b\n",
         "%b -> c\n",
@@ -255,7 +389,7 @@ mod test {
         "&inv -> a\n",
     );
 
-    const EXAMPLE_INPUT_2: &'static str = concat!(
+    const EXAMPLE_INPUT_2: &str = concat!(
         "broadcaster -> a\n",
         "%a -> inv, con\n",
         "&inv -> b\n",
@@ -268,4 +402,118 @@ mod test {
         assert_eq!(part_a(&EXAMPLE_INPUT_1.parse().unwrap()), 32_000_000);
         assert_eq!(part_a(&EXAMPLE_INPUT_2.parse().unwrap()), 11_687_500);
     }
+
+    #[test]
+    fn test_module_query() {
+        let cfg: Config = EXAMPLE_INPUT_1.parse().unwrap();
+        assert_eq!(cfg.modules().count(), 5);
+        assert_eq!(
+            cfg.get("broadcaster"),
+            Some(&Module::Broadcast(vec![
+                "a".to_string(),
+                "b".to_string(),
+                "c".to_string()
+            ]))
+        );
+        assert_eq!(cfg.get("output"), None);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let mut cfg: Config = EXAMPLE_INPUT_2.parse().unwrap();
+        let initial = cfg.snapshot();
+        assert_eq!(initial, "a: off\nb: off\ncon: a=low, b=low\ninv: a=low");
+
+        cfg.iter_signals_from_button_press().for_each(drop);
+        assert_eq!(
+            cfg.snapshot(),
+            "a: on\nb: on\ncon: a=high, b=high\ninv: a=high"
+        );
+
+        // The second example cycles back to where it started after four button presses
+        for _ in 0..3 {
+            cfg.iter_signals_from_button_press().for_each(drop);
+        }
+        assert_eq!(cfg.snapshot(), initial);
+
+        // The first example is back in the initial state after every button press
+        let mut cfg: Config = EXAMPLE_INPUT_1.parse().unwrap();
+        let initial = cfg.snapshot();
+        cfg.iter_signals_from_button_press().for_each(drop);
+        assert_eq!(cfg.snapshot(), initial);
+    }
+
+    #[test]
+    fn test_press_log() {
+        let mut cfg: Config = EXAMPLE_INPUT_1.parse().unwrap();
+        let log = press_log(&mut cfg);
+        assert_eq!(log.len(), 12);
+        assert_eq!(log.iter().filter(|(_, h, _)| *h).count(), 4);
+        assert_eq!(
+            log[0],
+            ("button".to_string(), false, "broadcaster".to_string())
+        );
+        assert_eq!(log[11], ("inv".to_string(), true, "a".to_string()));
+
+        // The network is back in its initial state, so the next press sends the same signals
+        assert_eq!(press_log(&mut cfg), log);
+    }
+
+    #[test]
+    fn test_first_high_press() {
+        let cfg: Config = EXAMPLE_INPUT_2.parse().unwrap();
+
+        // The output sees a high pulse from con on the very first press
+        assert_eq!(first_high_press(&cfg, "con"), Some(1));
+        assert_eq!(first_high_press(&cfg, "inv"), Some(2));
+
+        // The output isn't a module so it never emits anything
+        assert_eq!(first_high_press(&cfg, "output"), None);
+
+        // The first example is back where it started after one press, so it's quick to tell
+        // that the broadcaster never sends a high pulse
+        let cfg: Config = EXAMPLE_INPUT_1.parse().unwrap();
+        assert_eq!(first_high_press(&cfg, "a"), Some(1));
+        assert_eq!(first_high_press(&cfg, "broadcaster"), None);
+    }
+
+    // A scaled down version of the real input with two independent counters
+    const COUNTER_INPUT: &str = concat!(
+        "broadcaster -> a, c\n",
+        "%a -> b, ab\n",
+        "%b -> ab\n",
+        "&ab -> a, abi\n",
+        "&abi -> out\n",
+        "%c -> cd\n",
+        "&cd -> c, cdi\n",
+        "&cdi -> out\n",
+        "&out -> rx\n",
+    );
+
+    #[test]
+    fn test_counter_modules() {
+        let cfg: Config = COUNTER_INPUT.parse().unwrap();
+        assert_eq!(counter_modules(&cfg), vec!["abi", "cdi"]);
+        assert_eq!(
+            counter_modules(&EXAMPLE_INPUT_1.parse().unwrap()),
+            Vec::<String>::new()
+        );
+    }
+
+    #[test]
+    fn test_broadcaster_branches() {
+        let cfg: Config = COUNTER_INPUT.parse().unwrap();
+        let branches = broadcaster_branches(&cfg);
+        assert_eq!(branches, vec![vec!["a", "b"], vec!["c"]]);
+
+        // There is one branch for every counter
+        assert_eq!(branches.len(), counter_modules(&cfg).len());
+
+        // In the first example the chains overlap, but each broadcaster output still gets its own
+        let cfg: Config = EXAMPLE_INPUT_1.parse().unwrap();
+        assert_eq!(
+            broadcaster_branches(&cfg),
+            vec![vec!["a", "b", "c"], vec!["b", "c"], vec!["c"]]
+        );
+    }
 }
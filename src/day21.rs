@@ -1,15 +1,30 @@
+use crate::Part;
 use anyhow::{anyhow, Result};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::Path;
 use std::str::FromStr;
 
-struct Map {
+#[derive(Debug)]
+pub struct Map {
     width: isize,
     height: isize,
     start: (isize, isize),
     walls: HashSet<(isize, isize)>,
 }
 
+/// How the map behaves at its edges
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    /// Nothing exists beyond the edges of the map
+    Finite,
+
+    /// The map repeats infinitely in every direction
+    Infinite,
+
+    /// Walking off an edge of the map re-enters it on the opposite edge
+    Torus,
+}
+
 impl FromStr for Map {
     type Err = anyhow::Error;
 
@@ -50,55 +65,138 @@ impl FromStr for Map {
 }
 
 impl Map {
-    fn num_reachable_gardens(&self, step_limit: usize, infinite: bool) -> usize {
+    pub fn num_reachable_gardens(&self, step_limit: usize, topology: Topology) -> usize {
+        self.reachable_from(&[self.start], step_limit, topology)
+    }
+
+    /// Return the number of gardens where any gardener can end up after exactly the given number
+    /// of steps, when there is one gardener at each start position
+    pub fn reachable_from(
+        &self,
+        starts: &[(isize, isize)],
+        step_limit: usize,
+        topology: Topology,
+    ) -> usize {
+        // Gardeners at different positions may reach the same garden with different parities, so
+        // each one is tracked separately
+        let mut reachable = HashSet::new();
+        for &start in starts {
+            reachable.extend(self.reachable_gardens(start, step_limit, topology));
+        }
+        reachable.len()
+    }
+
+    /// Return the number of gardens reachable from the start after exactly each of the given
+    /// numbers of steps, using a single search up to the largest of them
+    pub fn counts_at(&self, checkpoints: &[usize], topology: Topology) -> Vec<usize> {
+        let step_limit = checkpoints.iter().copied().max().unwrap_or(0);
+        let distances = self.distances(self.start, step_limit, topology);
+        checkpoints
+            .iter()
+            .map(|&checkpoint| {
+                distances
+                    .iter()
+                    .filter(|&(&(_, parity), &steps)| {
+                        steps <= checkpoint && parity == checkpoint % 2
+                    })
+                    .count()
+            })
+            .collect()
+    }
+
+    fn reachable_gardens(
+        &self,
+        start: (isize, isize),
+        step_limit: usize,
+        topology: Topology,
+    ) -> impl Iterator<Item = (isize, isize)> {
+        self.distances(start, step_limit, topology)
+            .into_keys()
+            .filter(move |&(_, parity)| parity == step_limit % 2)
+            .map(|(pos, _)| pos)
+    }
+
+    /// Return the fewest number of steps needed to reach every garden that is at most the given
+    /// number of steps away from the start, for both parities of the step count. Once a garden is
+    /// reached it can be reached again every second step by walking back and forth.
+    ///
+    /// On finite and infinite maps every garden can only be reached with one parity, but on a
+    /// torus with an odd width or height a garden may be reached with the other parity by walking
+    /// around the wrap
+    fn distances(
+        &self,
+        start: (isize, isize),
+        step_limit: usize,
+        topology: Topology,
+    ) -> HashMap<((isize, isize), usize), usize> {
         let mut visited = HashMap::new();
-        visited.insert(self.start, 0);
+        visited.insert((start, 0), 0);
 
         let mut to_visit: VecDeque<((isize, isize), usize)> = VecDeque::new();
-        to_visit.push_back((self.start, 0));
+        to_visit.push_back((start, 0));
 
         while let Some(((x, y), steps)) = to_visit.pop_front() {
             if steps >= step_limit {
                 continue;
             }
-            let neighbors = if infinite {
-                [
-                    Some((x, y - 1)), // Up
-                    Some((x, y + 1)), // Down
-                    Some((x - 1, y)), // Left
-                    Some((x + 1, y)), // Right
-                ]
-            } else {
-                [
+            let neighbors = match topology {
+                Topology::Finite => [
                     (y > 0).then_some((x, y - 1)),               // Up
                     (y + 1 < self.height).then_some((x, y + 1)), // Down
                     (x > 0).then_some((x - 1, y)),               // Left
                     (x + 1 < self.width).then_some((x + 1, y)),  // Right
-                ]
+                ],
+                Topology::Infinite | Topology::Torus => [
+                    Some((x, y - 1)), // Up
+                    Some((x, y + 1)), // Down
+                    Some((x - 1, y)), // Left
+                    Some((x + 1, y)), // Right
+                ],
             };
             for n in neighbors.into_iter().flatten() {
                 let wrapped_n = (n.0.rem_euclid(self.width), n.1.rem_euclid(self.height));
-                if self.walls.contains(&wrapped_n) || visited.contains_key(&n) {
+
+                // On a torus we're always on the same tile, so we can only visit each spot once
+                // per parity
+                let n = if topology == Topology::Torus {
+                    wrapped_n
+                } else {
+                    n
+                };
+                let state = (n, (steps + 1) % 2);
+                if self.walls.contains(&wrapped_n) || visited.contains_key(&state) {
                     continue;
                 }
-                visited.insert(n, steps + 1);
+                visited.insert(state, steps + 1);
                 to_visit.push_back((n, steps + 1));
             }
         }
         visited
-            .into_values()
-            .filter(|&steps| steps % 2 == step_limit % 2)
-            .count()
     }
 }
 
 fn part_a(map: &Map) -> usize {
-    map.num_reachable_gardens(64, false)
+    map.num_reachable_gardens(64, Topology::Finite)
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let map = std::fs::read_to_string(path)?.parse()?;
-    Ok((part_a(&map), None))
+/// Parse the puzzle input
+pub fn parse(input: &str) -> Result<Map> {
+    input.parse()
+}
+
+/// Solve both parts using the parsed puzzle input
+pub fn solve(map: &Map, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part.includes_a().then(|| part_a(map));
+    Ok((a, None))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let map = parse(input)?;
+    solve(&map, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -107,7 +205,7 @@ mod test {
 
     test_real_input!(21, 3615);
 
-    const EXAMPLE_INPUT: &'static str = concat!(
+    const EXAMPLE_INPUT: &str = concat!(
         "...........\n",
         ".....###.#.\n",
         ".###.##..#.\n",
@@ -124,12 +222,106 @@ mod test {
     #[test]
     fn test_examples() {
         let map: Map = EXAMPLE_INPUT.parse().unwrap();
-        assert_eq!(map.num_reachable_gardens(6, false), 16);
-        assert_eq!(map.num_reachable_gardens(6, true), 16);
-        assert_eq!(map.num_reachable_gardens(10, true), 50);
-        assert_eq!(map.num_reachable_gardens(50, true), 1594);
-        assert_eq!(map.num_reachable_gardens(100, true), 6536);
-        assert_eq!(map.num_reachable_gardens(500, true), 167_004);
-        assert_eq!(map.num_reachable_gardens(1000, true), 668_697);
+        assert_eq!(map.num_reachable_gardens(6, Topology::Finite), 16);
+        assert_eq!(map.num_reachable_gardens(6, Topology::Infinite), 16);
+        assert_eq!(map.num_reachable_gardens(10, Topology::Infinite), 50);
+        assert_eq!(map.num_reachable_gardens(50, Topology::Infinite), 1594);
+        assert_eq!(map.num_reachable_gardens(100, Topology::Infinite), 6536);
+        assert_eq!(map.num_reachable_gardens(500, Topology::Infinite), 167_004);
+        assert_eq!(map.num_reachable_gardens(1000, Topology::Infinite), 668_697);
+    }
+
+    #[test]
+    fn test_counts_at() {
+        let map: Map = EXAMPLE_INPUT.parse().unwrap();
+        assert_eq!(
+            map.counts_at(&[6, 10, 50], Topology::Infinite),
+            vec![16, 50, 1594]
+        );
+        assert_eq!(
+            map.counts_at(&[64, 6], Topology::Finite),
+            vec![
+                map.num_reachable_gardens(64, Topology::Finite),
+                map.num_reachable_gardens(6, Topology::Finite),
+            ]
+        );
+        assert!(map.counts_at(&[], Topology::Infinite).is_empty());
+    }
+
+    #[test]
+    fn test_reachable_from() {
+        let map: Map = EXAMPLE_INPUT.parse().unwrap();
+        let one_start = map.reachable_from(&[map.start], 2, Topology::Finite);
+        assert_eq!(one_start, map.num_reachable_gardens(2, Topology::Finite));
+        assert_eq!(one_start, 4);
+
+        // The second gardener reaches gardens with the opposite parity of the first one
+        let two_starts = map.reachable_from(&[map.start, (4, 5)], 2, Topology::Finite);
+        assert!(two_starts > one_start);
+        assert_eq!(two_starts, 9);
+
+        // Rocks still block the way
+        let map: Map = "S#.\n##.\n...\n".parse().unwrap();
+        assert_eq!(
+            map.reachable_from(&[(0, 0), (2, 0)], 1, Topology::Finite),
+            1
+        );
+    }
+
+    /// Count the gardens reachable after exactly the given number of steps on a torus by moving
+    /// every gardener one step at a time
+    fn step_torus(map: &Map, steps: usize) -> usize {
+        let mut positions = HashSet::from([map.start]);
+        for _ in 0..steps {
+            positions = positions
+                .into_iter()
+                .flat_map(|(x, y)| [(x, y - 1), (x, y + 1), (x - 1, y), (x + 1, y)])
+                .map(|(x, y)| (x.rem_euclid(map.width), y.rem_euclid(map.height)))
+                .filter(|n| !map.walls.contains(n))
+                .collect();
+        }
+        positions.len()
+    }
+
+    #[test]
+    fn test_torus() {
+        // The only way to get anywhere is to walk off the left edge
+        let map: Map = "####\nS#..\n####\n".parse().unwrap();
+        assert_eq!(map.num_reachable_gardens(1, Topology::Finite), 0);
+        assert_eq!(map.num_reachable_gardens(2, Topology::Finite), 1);
+        assert_eq!(map.num_reachable_gardens(1, Topology::Torus), 1);
+        assert_eq!(map.num_reachable_gardens(2, Topology::Torus), 2);
+
+        // With an odd width a garden can be reached with both parities by walking around the wrap
+        let map: Map = "###\nS..\n###\n".parse().unwrap();
+        assert_eq!(map.num_reachable_gardens(1, Topology::Torus), 2);
+        assert_eq!(map.num_reachable_gardens(2, Topology::Torus), 3);
+        assert_eq!(map.num_reachable_gardens(3, Topology::Torus), 3);
+        assert_eq!(map.counts_at(&[1, 2, 3], Topology::Torus), vec![2, 3, 3]);
+        assert_eq!(map.reachable_from(&[(0, 1)], 2, Topology::Torus), 3);
+
+        // A single row also wraps onto itself vertically
+        let map: Map = "S..\n".parse().unwrap();
+        assert_eq!(map.num_reachable_gardens(1, Topology::Torus), 3);
+        assert_eq!(map.num_reachable_gardens(2, Topology::Torus), 3);
+
+        // Unlike the infinite map the torus never grows beyond the gardens of a single tile
+        let map: Map = EXAMPLE_INPUT.parse().unwrap();
+        assert_eq!(map.num_reachable_gardens(6, Topology::Torus), 16);
+        for steps in [6, 7, 10, 11, 25, 50] {
+            assert_eq!(
+                map.num_reachable_gardens(steps, Topology::Torus),
+                step_torus(&map, steps),
+                "{} steps",
+                steps,
+            );
+        }
+        assert_eq!(
+            map.counts_at(&[6, 7, 10, 11, 25, 50], Topology::Torus),
+            [6, 7, 10, 11, 25, 50]
+                .into_iter()
+                .map(|steps| step_torus(&map, steps))
+                .collect::<Vec<_>>(),
+        );
     }
 }
@@ -1,17 +1,18 @@
+use crate::Part;
 use anyhow::{anyhow, Result};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::Path;
 use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Coord {
+pub struct Coord {
     x: usize,
     y: usize,
     z: usize,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Cube {
+pub struct Cube {
     a: Coord,
     b: Coord,
 }
@@ -23,6 +24,10 @@ struct SupportInfo {
 }
 
 impl Cube {
+    fn volume(&self) -> usize {
+        (self.b.x - self.a.x + 1) * (self.b.y - self.a.y + 1) * (self.b.z - self.a.z + 1)
+    }
+
     fn iter_xy_plane(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
         (self.a.y..=self.b.y).flat_map(|y| (self.a.x..=self.b.x).map(move |x| (x, y)))
     }
@@ -32,6 +37,12 @@ impl Cube {
             .any(|self_xy| other.iter_xy_plane().any(|other_xy| self_xy == other_xy))
     }
 
+    /// Key that orders cubes from the lowest to the highest. Ties are broken on x and y so the
+    /// order doesn't depend on the order of the input
+    fn settle_order(&self) -> (usize, usize, usize, usize, usize, usize) {
+        (self.a.z, self.b.z, self.a.x, self.a.y, self.b.x, self.b.y)
+    }
+
     fn with_z(&self, z: usize) -> Self {
         Self {
             a: Coord {
@@ -100,9 +111,11 @@ impl FromStr for Coord {
     }
 }
 
-fn settle(falling_cubes: &[Cube]) -> Vec<Cube> {
+/// Let all cubes fall until they rest on the ground or another cube. The settled cubes are
+/// ordered from the lowest to the highest
+pub fn settle(falling_cubes: &[Cube]) -> Vec<Cube> {
     let mut sorted_cubes = falling_cubes.to_vec();
-    sorted_cubes.sort_by_key(|c| (c.a.z, c.b.z));
+    sorted_cubes.sort_by_key(Cube::settle_order);
 
     for i in 0..sorted_cubes.len() {
         let mut supported_at = 0;
@@ -115,10 +128,20 @@ fn settle(falling_cubes: &[Cube]) -> Vec<Cube> {
         sorted_cubes[i] = cube.with_z(supported_at + 1);
     }
 
-    sorted_cubes.sort_by_key(|c| (c.a.z, c.b.z));
+    sorted_cubes.sort_by_key(Cube::settle_order);
     sorted_cubes
 }
 
+/// Return the z coordinate of the highest point of any cube
+pub fn max_height(cubes: &[Cube]) -> usize {
+    cubes.iter().map(|c| c.b.z).max().unwrap_or(0)
+}
+
+/// Return the total number of unit cubes that make up all cubes
+pub fn total_volume(cubes: &[Cube]) -> usize {
+    cubes.iter().map(Cube::volume).sum()
+}
+
 fn part_a(falling_cubes: &[Cube]) -> usize {
     let settled_cubes = settle(falling_cubes);
     let support_info = SupportInfo::support_graph(&settled_cubes);
@@ -140,12 +163,14 @@ fn part_a(falling_cubes: &[Cube]) -> usize {
     num_removable
 }
 
-fn part_b(falling_cubes: &[Cube]) -> usize {
-    let settled_cubes = settle(falling_cubes);
-    let support_info = SupportInfo::support_graph(&settled_cubes);
+/// Return how many other cubes would fall if each of the settled cubes was removed, in the same
+/// order as the given cubes
+pub fn fall_counts(settled_cubes: &[Cube]) -> Vec<usize> {
+    let support_info = SupportInfo::support_graph(settled_cubes);
 
-    let mut num_chains = 0;
+    let mut counts = Vec::with_capacity(settled_cubes.len());
     for cube_to_remove in 0..settled_cubes.len() {
+        let mut num_falling = 0;
         let mut to_visit = VecDeque::new();
         let mut visited = HashSet::new();
         to_visit.push_back(cube_to_remove);
@@ -162,23 +187,52 @@ fn part_b(falling_cubes: &[Cube]) -> usize {
                     == 0
                     && visited.insert(supported_cube)
                 {
-                    num_chains += 1;
+                    num_falling += 1;
                     to_visit.push_back(supported_cube);
                 }
             }
         }
+        counts.push(num_falling);
     }
-    num_chains
+    counts
+}
+
+/// Return every `(supporter, supported)` pair of settled cubes where the first cube rests directly
+/// below the second one, ordered by the supporter and then the supported cube
+pub fn support_edges(settled_cubes: &[Cube]) -> Vec<(usize, usize)> {
+    let support_info = SupportInfo::support_graph(settled_cubes);
+
+    let mut edges = support_info
+        .iter()
+        .flat_map(|(&supporter, info)| info.supports.iter().map(move |&s| (supporter, s)))
+        .collect::<Vec<_>>();
+    edges.sort();
+    edges
+}
+
+fn part_b(falling_cubes: &[Cube]) -> usize {
+    fall_counts(&settle(falling_cubes)).into_iter().sum()
 }
 
-fn parse_cubes(s: &str) -> Result<Vec<Cube>> {
+/// Parse the puzzle input
+pub fn parse(s: &str) -> Result<Vec<Cube>> {
     s.lines().map(|l| l.parse()).collect()
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let cubes_str = std::fs::read_to_string(path)?;
-    let cubes = parse_cubes(&cubes_str)?;
-    Ok((part_a(&cubes), part_b(&cubes).into()))
+/// Solve both parts using the parsed puzzle input
+pub fn solve(cubes: &[Cube], part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part.includes_a().then(|| part_a(cubes));
+    let b = part.includes_b().then(|| part_b(cubes));
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let cubes = parse(input)?;
+    solve(&cubes, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -187,7 +241,7 @@ mod test {
 
     test_real_input!(22, 407, 59266);
 
-    const EXAMPLE_INPUT: &'static str = concat!(
+    const EXAMPLE_INPUT: &str = concat!(
         "1,0,1~1,2,1\n",
         "0,0,2~2,0,2\n",
         "0,2,3~2,2,3\n",
@@ -197,13 +251,54 @@ mod test {
         "1,1,8~1,1,9\n",
     );
 
+    #[test]
+    fn test_stats() {
+        let cubes = parse(EXAMPLE_INPUT).unwrap();
+        let settled_cubes = settle(&cubes);
+        assert_eq!(total_volume(&cubes), 20);
+        assert_eq!(total_volume(&settled_cubes), total_volume(&cubes));
+        assert_eq!(max_height(&cubes), 9);
+        assert_eq!(max_height(&settled_cubes), 6);
+    }
+
+    #[test]
+    fn test_settle_is_order_independent() {
+        let cubes = parse(EXAMPLE_INPUT).unwrap();
+        let mut shuffled_lines = EXAMPLE_INPUT.lines().collect::<Vec<_>>();
+        shuffled_lines.reverse();
+        shuffled_lines.swap(0, 3);
+        let shuffled_cubes = parse(&shuffled_lines.join("\n")).unwrap();
+
+        assert_eq!(settle(&cubes), settle(&shuffled_cubes));
+        assert_eq!(part_a(&shuffled_cubes), 5);
+        assert_eq!(part_b(&shuffled_cubes), 7);
+    }
+
     #[test]
     fn test_part_a() {
-        assert_eq!(part_a(&parse_cubes(EXAMPLE_INPUT).unwrap()), 5);
+        assert_eq!(part_a(&parse(EXAMPLE_INPUT).unwrap()), 5);
+    }
+
+    #[test]
+    fn test_fall_counts() {
+        let settled_cubes = settle(&parse(EXAMPLE_INPUT).unwrap());
+        let counts = fall_counts(&settled_cubes);
+        assert_eq!(counts.iter().sum::<usize>(), 7);
+        assert_eq!(counts.iter().max(), Some(&counts[0]));
+        assert_eq!(counts, vec![6, 0, 0, 0, 0, 1, 0]);
+    }
+
+    #[test]
+    fn test_support_edges() {
+        let settled_cubes = settle(&parse(EXAMPLE_INPUT).unwrap());
+        let edges = support_edges(&settled_cubes);
+        assert_eq!(edges.len(), 9);
+        assert_eq!(&edges[..2], &[(0, 1), (0, 2)]);
+        assert_eq!(edges.last(), Some(&(5, 6)));
     }
 
     #[test]
     fn test_part_b() {
-        assert_eq!(part_b(&parse_cubes(EXAMPLE_INPUT).unwrap()), 7);
+        assert_eq!(part_b(&parse(EXAMPLE_INPUT).unwrap()), 7);
     }
 }
@@ -1,17 +1,18 @@
+use crate::direction::Direction;
+use crate::grid::parse_sparse;
+use crate::Part;
 use anyhow::{anyhow, Result};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::Path;
 use std::str::FromStr;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+enum Tile {
+    Forest,
+    Slope(Direction),
 }
 
-struct Map {
+#[derive(Debug)]
+pub struct Map {
     width: usize,
     height: usize,
     forest: HashSet<(usize, usize)>,
@@ -22,36 +23,27 @@ impl FromStr for Map {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        let mut width = 0;
-        let mut height = 0;
+        let (width, height, tiles) = parse_sparse(s, |c| match c {
+            '#' => Ok(Some(Tile::Forest)),
+            '^' => Ok(Some(Tile::Slope(Direction::Up))),
+            'v' => Ok(Some(Tile::Slope(Direction::Down))),
+            '<' => Ok(Some(Tile::Slope(Direction::Left))),
+            '>' => Ok(Some(Tile::Slope(Direction::Right))),
+            '.' => Ok(None),
+            _ => Err(anyhow!("Unknown character in map {:?}", c)),
+        })?;
+
         let mut forest = HashSet::new();
         let mut slopes = HashMap::new();
-
-        for (y, line) in s.lines().enumerate() {
-            for (x, c) in line.chars().enumerate() {
-                let p = (x, y);
-                match c {
-                    '#' => {
-                        forest.insert(p);
-                    }
-                    '^' => {
-                        slopes.insert(p, Direction::Up);
-                    }
-                    'v' => {
-                        slopes.insert(p, Direction::Down);
-                    }
-                    '<' => {
-                        slopes.insert(p, Direction::Left);
-                    }
-                    '>' => {
-                        slopes.insert(p, Direction::Right);
-                    }
-                    '.' => {}
-                    _ => return Err(anyhow!("Unknown character in map {:?}", c)),
+        for (p, tile) in tiles {
+            match tile {
+                Tile::Forest => {
+                    forest.insert(p);
+                }
+                Tile::Slope(d) => {
+                    slopes.insert(p, d);
                 }
-                width = x + 1;
             }
-            height = y + 1;
         }
         Ok(Self {
             width,
@@ -62,16 +54,24 @@ impl FromStr for Map {
     }
 }
 
-fn part_a(map: &Map) -> usize {
+impl Map {
+    /// Return true if any tile of the map is a slope that can only be traversed in one direction
+    pub fn has_slopes(&self) -> bool {
+        !self.slopes.is_empty()
+    }
+}
+
+/// Call the function with the length of every path from the start to the end that follows the
+/// slopes and never visits the same tile twice
+fn for_each_slope_path(map: &Map, mut f: impl FnMut(usize)) {
     #[allow(clippy::type_complexity)]
     let mut to_visit: VecDeque<(usize, usize, HashSet<(usize, usize)>)> = VecDeque::new();
     to_visit.push_back((1, 0, [(1, 0)].into_iter().collect()));
 
-    let mut longest_path = 0;
     while let Some((x, y, visited)) = to_visit.pop_front() {
         if (x, y) == (map.width - 2, map.height - 1) {
             // -1 because our visited set includes the starting tile
-            longest_path = longest_path.max(visited.len() - 1);
+            f(visited.len() - 1);
             continue;
         }
 
@@ -94,11 +94,21 @@ fn part_a(map: &Map) -> usize {
             to_visit.push_back((nx, ny, neighbor_visited));
         }
     }
+}
 
+fn part_a(map: &Map) -> usize {
+    let mut longest_path = 0;
+    for_each_slope_path(map, |len| longest_path = longest_path.max(len));
     longest_path
 }
 
-fn part_b(map: &Map) -> usize {
+/// Map from each junction to the junctions it connects directly to, along with the length of the
+/// longest trail between them
+pub type Graph = HashMap<(usize, usize), HashMap<(usize, usize), usize>>;
+
+/// Build a graph of all junctions in the map, ignoring slopes. The start and end tiles are
+/// included as junctions
+pub fn junction_graph(map: &Map) -> Graph {
     let source = (1usize, 0usize);
     let target = (map.width - 2, map.height - 1);
 
@@ -108,7 +118,7 @@ fn part_b(map: &Map) -> usize {
     to_visit.push((source.0, source.1, 0, source));
     visited.insert(target);
 
-    let mut graph: HashMap<(usize, usize), HashMap<(usize, usize), usize>> = HashMap::new();
+    let mut graph: Graph = HashMap::new();
     while let Some((x, y, mut cost, mut branch_start)) = to_visit.pop() {
         let neighbors: Vec<(usize, usize)> = [
             y.checked_sub(1).map(|ny| (x, ny)),
@@ -150,30 +160,88 @@ fn part_b(map: &Map) -> usize {
         }
     }
 
+    graph
+}
+
+/// Return the sum of all edge weights in the graph. No path that visits every junction at most
+/// once can be longer than this
+pub fn upper_bound(graph: &Graph) -> usize {
+    // Every edge is stored in both directions
+    graph
+        .values()
+        .flat_map(|edges| edges.values())
+        .sum::<usize>()
+        / 2
+}
+
+/// Call the function with the length of every path from the start to the end that ignores the
+/// slopes and never visits the same junction twice
+fn for_each_junction_path(map: &Map, mut f: impl FnMut(usize)) {
+    let source = (1usize, 0usize);
+    let target = (map.width - 2, map.height - 1);
+    let graph = junction_graph(map);
+
     // Find the most expensive path in the graph using brute force
     #[allow(clippy::type_complexity)]
     let mut to_visit: Vec<(usize, usize, usize, HashSet<(usize, usize)>)> = Vec::new();
     to_visit.push((source.0, source.1, 0, HashSet::new()));
 
-    let mut max_cost = 0;
     while let Some((x, y, acc_cost, mut visited)) = to_visit.pop() {
         if !visited.insert((x, y)) {
             continue;
         }
         if (x, y) == target {
-            max_cost = max_cost.max(acc_cost);
+            f(acc_cost);
             continue;
         }
         for (n, cost) in graph.get(&(x, y)).unwrap().iter() {
             to_visit.push((n.0, n.1, acc_cost + cost, visited.clone()));
         }
     }
+}
+
+fn part_b(map: &Map) -> usize {
+    let mut max_cost = 0;
+    for_each_junction_path(map, |cost| max_cost = max_cost.max(cost));
     max_cost
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let map: Map = std::fs::read_to_string(path)?.parse()?;
-    Ok((part_a(&map), part_b(&map).into()))
+/// Count the distinct paths from the start to the end that are at least `min_len` steps long.
+/// When slopes are ignored, paths are distinct if they visit different junctions
+pub fn paths_at_least(map: &Map, min_len: usize, respect_slopes: bool) -> usize {
+    let mut num_paths = 0;
+    let count = |len| {
+        if len >= min_len {
+            num_paths += 1;
+        }
+    };
+    if respect_slopes {
+        for_each_slope_path(map, count);
+    } else {
+        for_each_junction_path(map, count);
+    }
+    num_paths
+}
+
+/// Parse the puzzle input
+pub fn parse(input: &str) -> Result<Map> {
+    input.parse()
+}
+
+/// Solve both parts using the parsed puzzle input
+pub fn solve(map: &Map, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part.includes_a().then(|| part_a(map));
+    let b = part.includes_b().then(|| part_b(map));
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let map = parse(input)?;
+    solve(&map, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -188,7 +256,7 @@ mod test {
         6226
     );
 
-    const EXAMPLE_INPUT: &'static str = concat!(
+    const EXAMPLE_INPUT: &str = concat!(
         "#.#####################\n",
         "#.......#########...###\n",
         "#######.#########.#.###\n",
@@ -219,6 +287,39 @@ mod test {
         assert_eq!(part_a(&EXAMPLE_INPUT.parse().unwrap()), 94);
     }
 
+    #[test]
+    fn test_has_slopes() {
+        let map: Map = EXAMPLE_INPUT.parse().unwrap();
+        assert!(map.has_slopes());
+
+        let flat_map: Map = EXAMPLE_INPUT
+            .replace(['^', 'v', '<', '>'], ".")
+            .parse()
+            .unwrap();
+        assert!(!flat_map.has_slopes());
+    }
+
+    #[test]
+    fn test_upper_bound() {
+        let map: Map = EXAMPLE_INPUT.parse().unwrap();
+        let bound = upper_bound(&junction_graph(&map));
+        assert!(part_b(&map) <= bound);
+        assert_eq!(bound, 216);
+    }
+
+    #[test]
+    fn test_paths_at_least() {
+        let map: Map = EXAMPLE_INPUT.parse().unwrap();
+        assert_eq!(paths_at_least(&map, 0, true), 6);
+        assert_eq!(paths_at_least(&map, 94, true), 1);
+        assert_eq!(paths_at_least(&map, 95, true), 0);
+
+        // Without slopes there are more paths, and some of them are longer
+        assert!(paths_at_least(&map, 154, false) >= 1);
+        assert_eq!(paths_at_least(&map, 155, false), 0);
+        assert!(paths_at_least(&map, 0, false) > paths_at_least(&map, 0, true));
+    }
+
     #[test]
     fn test_part_b() {
         assert_eq!(part_b(&EXAMPLE_INPUT.parse().unwrap()), 154);
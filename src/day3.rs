@@ -1,11 +1,14 @@
+use crate::grid::Grid;
+use crate::Part;
 use anyhow::Result;
-use std::collections::{HashMap, HashSet};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::Path;
 
 #[derive(Debug)]
-struct Schematic {
+pub struct Schematic {
+    /// The raw characters of the schematic
+    grid: Grid<char>,
+
     /// Ordered list of numbers as they appear in the schematic
     nums: Vec<usize>,
 
@@ -17,23 +20,25 @@ struct Schematic {
 }
 
 impl Schematic {
-    fn parse(schematic: &[Vec<char>]) -> Self {
+    pub fn parse(grid: Grid<char>) -> Self {
         // Find all symbols in the first pass
-        let symbols = schematic
-            .iter()
-            .enumerate()
-            .flat_map(|(y, line)| {
-                line.iter().enumerate().filter_map(move |(x, c)| {
-                    (*c != '.' && c.is_ascii_punctuation()).then_some(((x, y), *c))
-                })
-            })
-            .collect();
+        let mut symbols = HashMap::new();
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                let c = grid.get(x, y).copied().unwrap();
+                if c != '.' && c.is_ascii_punctuation() {
+                    symbols.insert((x, y), c);
+                }
+            }
+        }
 
         // Find all numbers and their positions in the second pass
         let mut nums = Vec::new();
         let mut pos_to_num_ids = HashMap::new();
-        for (y, line) in schematic.iter().enumerate() {
-            let mut line_iter = line.iter().enumerate().peekable();
+        for y in 0..grid.height() {
+            let mut line_iter = (0..grid.width())
+                .map(|x| (x, grid.get(x, y).copied().unwrap()))
+                .peekable();
             loop {
                 // If we find a digit, try to extract all the entire number
                 let num_id = nums.len();
@@ -56,6 +61,7 @@ impl Schematic {
         }
 
         Self {
+            grid,
             nums,
             pos_to_num_ids,
             symbols,
@@ -66,7 +72,10 @@ impl Schematic {
     fn adjacent_numbers<T: IntoIterator<Item = (usize, usize)>>(&self, positions: T) -> Vec<usize> {
         let mut nums = Vec::new();
         let mut used_nums = HashSet::new();
-        for pos in positions.into_iter().flat_map(iter_adjacent) {
+        for pos in positions
+            .into_iter()
+            .flat_map(|(x, y)| self.grid.neighbors8(x, y))
+        {
             let Some(number_id) = self.pos_to_num_ids.get(&pos).copied() else {
                 continue;
             };
@@ -76,46 +85,85 @@ impl Schematic {
         }
         nums
     }
-}
 
-/// Return all adjacent cells to the given position
-fn iter_adjacent((x, y): (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
-    (y.saturating_sub(1)..=(y + 1)).flat_map(move |ny| {
-        (x.saturating_sub(1)..=(x + 1))
-            .filter_map(move |nx| (x != nx || y != ny).then_some((nx, ny)))
-    })
+    /// Lazily yield every number that is adjacent to at least one symbol
+    pub fn part_numbers(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut used_nums = HashSet::new();
+        self.symbols
+            .keys()
+            .flat_map(|&(x, y)| self.grid.neighbors8(x, y))
+            .filter_map(|pos| self.pos_to_num_ids.get(&pos).copied())
+            .filter(move |&number_id| used_nums.insert(number_id))
+            .map(|number_id| self.nums[number_id])
+    }
+
+    /// Lazily yield the gear ratio of every `*` that is adjacent to exactly two numbers
+    pub fn gear_ratios(&self) -> impl Iterator<Item = usize> + '_ {
+        self.symbols
+            .iter()
+            .filter(|(_, &symbol)| symbol == '*')
+            .filter_map(|(pos, _)| match self.adjacent_numbers([*pos])[..] {
+                [a, b] => Some(a * b),
+                _ => None,
+            })
+    }
+
+    /// Return every part number in the order they appear, along with the distinct symbols it
+    /// touches in sorted order
+    pub fn part_numbers_with_symbols(&self) -> Vec<(usize, Vec<char>)> {
+        let mut num_symbols: BTreeMap<usize, BTreeSet<char>> = BTreeMap::new();
+        for (&(x, y), &symbol) in self.symbols.iter() {
+            for pos in self.grid.neighbors8(x, y) {
+                if let Some(&number_id) = self.pos_to_num_ids.get(&pos) {
+                    num_symbols.entry(number_id).or_default().insert(symbol);
+                }
+            }
+        }
+        num_symbols
+            .into_iter()
+            .map(|(number_id, symbols)| (self.nums[number_id], symbols.into_iter().collect()))
+            .collect()
+    }
+
+    /// Return a map from number index to how many of that number's digits touch the given position
+    pub fn adjacency_count(&self, (x, y): (usize, usize)) -> HashMap<usize, usize> {
+        let mut counts = HashMap::new();
+        for adjacent_pos in self.grid.neighbors8(x, y) {
+            if let Some(&number_id) = self.pos_to_num_ids.get(&adjacent_pos) {
+                *counts.entry(number_id).or_default() += 1;
+            }
+        }
+        counts
+    }
 }
 
 fn part_a(schematic: &Schematic) -> usize {
-    schematic
-        .adjacent_numbers(schematic.symbols.keys().copied())
-        .into_iter()
-        .sum()
+    schematic.part_numbers().sum()
 }
 
 fn part_b(schematic: &Schematic) -> usize {
-    let mut sum = 0;
-    for (pos, symbol) in schematic.symbols.iter() {
-        if *symbol != '*' {
-            continue;
-        }
-        let adjacent_numbers: Vec<usize> = schematic.adjacent_numbers([*pos]);
-        if adjacent_numbers.len() == 2 {
-            sum += adjacent_numbers[0] * adjacent_numbers[1];
-        }
-    }
-    sum
+    schematic.gear_ratios().sum()
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let file = File::open(path)?;
-    let raw_schematic = BufReader::new(file)
-        .lines()
-        .map(|l| Ok(l?.chars().collect()))
-        .collect::<Result<Vec<Vec<char>>, anyhow::Error>>()?;
-    let schematic = Schematic::parse(&raw_schematic);
+/// Parse the puzzle input
+pub fn parse(input: &str) -> Result<Schematic> {
+    Ok(Schematic::parse(Grid::from_str_with(input, Ok)?))
+}
+
+/// Solve both parts using the parsed puzzle input
+pub fn solve(schematic: &Schematic, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part.includes_a().then(|| part_a(schematic));
+    let b = part.includes_b().then(|| part_b(schematic));
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let schematic = parse(input)?;
+    solve(&schematic, part)
+}
 
-    Ok((part_a(&schematic), Some(part_b(&schematic))))
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -125,22 +173,19 @@ mod test {
     test_real_input!(3, 557_705, 84_266_818);
 
     fn example_schematic() -> Schematic {
-        let raw_schematic = [
-            "467..114..",
-            "...*......",
-            "..35..633.",
-            "......#...",
-            "617*......",
-            ".....+.58.",
-            "..592.....",
-            "......755.",
-            "...$.*....",
-            ".664.598..",
-        ]
-        .into_iter()
-        .map(|l| l.chars().collect())
-        .collect::<Vec<_>>();
-        Schematic::parse(&raw_schematic)
+        let raw_schematic = concat!(
+            "467..114..\n",
+            "...*......\n",
+            "..35..633.\n",
+            "......#...\n",
+            "617*......\n",
+            ".....+.58.\n",
+            "..592.....\n",
+            "......755.\n",
+            "...$.*....\n",
+            ".664.598..\n",
+        );
+        Schematic::parse(Grid::from_str_with(raw_schematic, Ok).unwrap())
     }
 
     #[test]
@@ -148,6 +193,44 @@ mod test {
         assert_eq!(part_a(&example_schematic()), 4361);
     }
 
+    #[test]
+    fn test_iterators() {
+        let schematic = example_schematic();
+
+        let mut part_numbers = schematic.part_numbers().collect::<Vec<_>>();
+        part_numbers.sort();
+        assert_eq!(part_numbers, vec![35, 467, 592, 598, 617, 633, 664, 755]);
+        assert_eq!(schematic.part_numbers().sum::<usize>(), 4361);
+
+        let mut gear_ratios = schematic.gear_ratios().collect::<Vec<_>>();
+        gear_ratios.sort();
+        assert_eq!(gear_ratios, vec![16345, 451490]);
+        assert_eq!(schematic.gear_ratios().sum::<usize>(), 467835);
+    }
+
+    #[test]
+    fn test_number_touching_symbol_twice() {
+        let schematic = Schematic::parse(Grid::from_str_with("467..\n*....\n", Ok).unwrap());
+
+        // Both 4 and 6 touch the same symbol, but the number must only be counted once
+        assert_eq!(schematic.adjacency_count((0, 1)), HashMap::from([(0, 2)]));
+        assert_eq!(part_a(&schematic), 467);
+    }
+
+    #[test]
+    fn test_part_numbers_with_symbols() {
+        let schematic = Schematic::parse(Grid::from_str_with("*...\n.12#\n....\n", Ok).unwrap());
+        assert_eq!(
+            schematic.part_numbers_with_symbols(),
+            vec![(12, vec!['#', '*'])]
+        );
+
+        let with_symbols = example_schematic().part_numbers_with_symbols();
+        assert_eq!(with_symbols.len(), 8);
+        assert_eq!(with_symbols[0], (467, vec!['*']));
+        assert!(with_symbols.contains(&(592, vec!['+'])));
+    }
+
     #[test]
     fn test_part_b() {
         assert_eq!(part_b(&example_schematic()), 467835);
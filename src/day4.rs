@@ -1,11 +1,10 @@
+use crate::Part;
 use anyhow::{anyhow, Result};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 use std::path::Path;
 use std::str::FromStr;
 
 #[derive(Debug)]
-struct Card {
+pub struct Card {
     nums: Vec<usize>,
     winning_nums: Vec<usize>,
 }
@@ -78,13 +77,25 @@ fn part_b(cards: &[Card]) -> usize {
     card_multiplier.into_iter().sum()
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let file = File::open(path)?;
-    let cards = BufReader::new(file)
-        .lines()
-        .map(|l| l?.parse())
-        .collect::<Result<Vec<Card>, _>>()?;
-    Ok((part_a(&cards), part_b(&cards).into()))
+/// Parse the puzzle input
+pub fn parse(s: &str) -> Result<Vec<Card>> {
+    s.lines().map(|l| l.parse()).collect()
+}
+
+/// Solve both parts using the parsed puzzle input
+pub fn solve(cards: &[Card], part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part.includes_a().then(|| part_a(cards));
+    let b = part.includes_b().then(|| part_b(cards));
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let cards = parse(input)?;
+    solve(&cards, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
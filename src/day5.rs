@@ -1,9 +1,10 @@
+use crate::Part;
 use anyhow::{anyhow, Result};
 use std::path::Path;
 use std::str::FromStr;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Almanac {
+pub struct Almanac {
     seeds: Vec<usize>,
     seed_to_soil: RangeSet,
     soil_to_fertilizer: RangeSet,
@@ -22,7 +23,7 @@ struct Range {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct RangeSet {
+pub struct RangeSet {
     ranges: Vec<Range>,
 }
 
@@ -33,7 +34,7 @@ impl RangeSet {
         Self { ranges }
     }
 
-    fn map(&self, src: usize) -> usize {
+    pub fn map(&self, src: usize) -> usize {
         for r in self.ranges.iter() {
             if (r.src..r.src + r.len).contains(&src) {
                 return r.dst + src - r.src;
@@ -41,6 +42,200 @@ impl RangeSet {
         }
         src
     }
+
+    /// Return the source value that is mapped onto the given destination. Destinations outside of
+    /// every range are passed through unchanged
+    pub fn unmap(&self, dst: usize) -> usize {
+        for r in self.ranges.iter() {
+            if (r.dst..r.dst + r.len).contains(&dst) {
+                return r.src + dst - r.dst;
+            }
+        }
+        dst
+    }
+
+    /// Split the half-open interval into pieces that are mapped by the same range. Each piece is
+    /// returned as its source start, source end and destination start
+    fn split_interval(&self, (start, end): (usize, usize)) -> Vec<(usize, usize, usize)> {
+        let mut pieces = Vec::new();
+        let mut cur = start;
+        for r in self.ranges.iter() {
+            if r.src + r.len <= cur {
+                continue;
+            }
+            if r.src >= end {
+                break;
+            }
+
+            // The gap before this range is mapped onto itself
+            if r.src > cur {
+                pieces.push((cur, r.src, cur));
+                cur = r.src;
+            }
+
+            let piece_end = end.min(r.src + r.len);
+            pieces.push((cur, piece_end, r.dst + cur - r.src));
+            cur = piece_end;
+        }
+        if cur < end {
+            pieces.push((cur, end, cur));
+        }
+        pieces
+    }
+
+    /// Map every `(start, len)` interval through the set. Intervals that straddle the boundary of a
+    /// range are split, so the result may contain more intervals than the input
+    pub fn map_ranges(&self, input: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+        input
+            .into_iter()
+            .flat_map(|(start, len)| self.split_interval((start, start + len)))
+            .map(|(src_start, src_end, dst_start)| (dst_start, src_end - src_start))
+            .collect()
+    }
+
+    /// Return a single set that maps every value the same way as applying this set followed by the
+    /// next one
+    pub fn compose(&self, next: &RangeSet) -> RangeSet {
+        let mut ranges = Vec::new();
+        for r in self.ranges.iter() {
+            for (start, end, dst) in next.split_interval((r.dst, r.dst + r.len)) {
+                ranges.push(Range {
+                    src: r.src + start - r.dst,
+                    len: end - start,
+                    dst,
+                });
+            }
+        }
+
+        // Values that this set maps onto themselves are only remapped by the next set
+        let coverage = self.coverage();
+        for n in next.ranges.iter() {
+            let mut gaps = Vec::new();
+            let mut cur = n.src;
+            let end = n.src + n.len;
+            for &(covered_start, covered_end) in coverage.iter() {
+                if covered_end <= cur {
+                    continue;
+                }
+                if covered_start >= end {
+                    break;
+                }
+                if covered_start > cur {
+                    gaps.push((cur, covered_start));
+                }
+                cur = covered_end;
+            }
+            if cur < end {
+                gaps.push((cur, end));
+            }
+
+            for (start, end) in gaps {
+                ranges.push(Range {
+                    src: start,
+                    len: end - start,
+                    dst: n.dst + start - n.src,
+                });
+            }
+        }
+        RangeSet::new(&ranges)
+    }
+
+    /// Return the sorted and merged half-open source intervals that are remapped by this set.
+    /// Anything outside of them is mapped onto itself
+    pub fn coverage(&self) -> Vec<(usize, usize)> {
+        let mut intervals: Vec<(usize, usize)> = Vec::new();
+        for r in self.ranges.iter() {
+            match intervals.last_mut() {
+                Some((_, end)) if *end >= r.src => *end = (*end).max(r.src + r.len),
+                _ => intervals.push((r.src, r.src + r.len)),
+            }
+        }
+        intervals
+    }
+}
+
+impl Almanac {
+    fn maps(&self) -> [&RangeSet; 7] {
+        [
+            &self.seed_to_soil,
+            &self.soil_to_fertilizer,
+            &self.fertilizer_to_water,
+            &self.water_to_light,
+            &self.light_to_temperature,
+            &self.temperature_to_humidity,
+            &self.humidity_to_location,
+        ]
+    }
+
+    /// Check that no seed range or map range extends beyond the largest possible value, which would
+    /// make mapping silently wrap around
+    pub fn validate(&self) -> Result<()> {
+        for s in self.seeds.chunks(2) {
+            if let [start, len] = *s {
+                if start.checked_add(len).is_none() {
+                    return Err(anyhow!(
+                        "Seed range starting at {} with length {} is out of bounds",
+                        start,
+                        len
+                    ));
+                }
+            }
+        }
+
+        let names = [
+            "seed-to-soil",
+            "soil-to-fertilizer",
+            "fertilizer-to-water",
+            "water-to-light",
+            "light-to-temperature",
+            "temperature-to-humidity",
+            "humidity-to-location",
+        ];
+        for (name, m) in names.into_iter().zip(self.maps()) {
+            for r in m.ranges.iter() {
+                if r.src.checked_add(r.len).is_none() || r.dst.checked_add(r.len).is_none() {
+                    return Err(anyhow!(
+                        "Range {} {} {} in the {} map is out of bounds",
+                        r.dst,
+                        r.src,
+                        r.len,
+                        name
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn seed_to_location(&self, seed: usize) -> usize {
+        self.maps().into_iter().fold(seed, |value, m| m.map(value))
+    }
+
+    /// Return the seed that ends up at the given location, by walking the maps in reverse
+    pub fn location_to_seed(&self, location: usize) -> usize {
+        self.maps()
+            .into_iter()
+            .rev()
+            .fold(location, |value, m| m.unmap(value))
+    }
+
+    /// Return the lowest location of any seed in the `(start, len)` interval
+    fn lowest_location_in(&self, (start, len): (usize, usize)) -> usize {
+        self.maps()
+            .into_iter()
+            .fold(vec![(start, len)], |ranges, m| m.map_ranges(ranges))
+            .into_iter()
+            .map(|(location, _)| location)
+            .min()
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Return a single set that maps seeds directly to their locations
+    pub fn seed_to_location_map(&self) -> RangeSet {
+        let [first, rest @ ..] = self.maps();
+        rest.into_iter()
+            .fold(first.clone(), |acc, m| acc.compose(m))
+    }
 }
 
 impl FromStr for Almanac {
@@ -141,54 +336,110 @@ impl FromStr for Range {
 fn part_a(almanac: &Almanac) -> usize {
     let mut lowest_location = usize::MAX;
     for seed in almanac.seeds.iter().copied() {
-        let soil = almanac.seed_to_soil.map(seed);
-        let fertilizer = almanac.soil_to_fertilizer.map(soil);
-        let water = almanac.fertilizer_to_water.map(fertilizer);
-        let light = almanac.water_to_light.map(water);
-        let temperature = almanac.light_to_temperature.map(light);
-        let humidity = almanac.temperature_to_humidity.map(temperature);
-        let location = almanac.humidity_to_location.map(humidity);
-        lowest_location = lowest_location.min(location);
+        lowest_location = lowest_location.min(almanac.seed_to_location(seed));
     }
     lowest_location
 }
 
+fn seed_ranges(almanac: &Almanac) -> impl Iterator<Item = (usize, usize)> + '_ {
+    almanac
+        .seeds
+        .chunks(2)
+        .filter(|s| s.len() == 2 && s[1] > 0)
+        .map(|s| (s[0], s[1]))
+}
+
+#[cfg(any(test, not(feature = "rayon")))]
+fn part_b_serial(almanac: &Almanac) -> usize {
+    seed_ranges(almanac)
+        .map(|r| almanac.lowest_location_in(r))
+        .min()
+        .unwrap_or(usize::MAX)
+}
+
+/// Every seed range is mapped independently, which makes them easy to spread across threads
+#[cfg(feature = "rayon")]
+fn part_b_parallel(almanac: &Almanac) -> usize {
+    use rayon::prelude::*;
+    seed_ranges(almanac)
+        .collect::<Vec<_>>()
+        .into_par_iter()
+        .map(|r| almanac.lowest_location_in(r))
+        .min()
+        .unwrap_or(usize::MAX)
+}
+
 fn part_b(almanac: &Almanac) -> usize {
-    let mut lowest_location = usize::MAX;
-    for s in almanac.seeds.chunks(2) {
-        for seed in s[0]..s[0] + s[1] {
-            let soil = almanac.seed_to_soil.map(seed);
-            let fertilizer = almanac.soil_to_fertilizer.map(soil);
-            let water = almanac.fertilizer_to_water.map(fertilizer);
-            let light = almanac.water_to_light.map(water);
-            let temperature = almanac.light_to_temperature.map(light);
-            let humidity = almanac.temperature_to_humidity.map(temperature);
-            let location = almanac.humidity_to_location.map(humidity);
-            lowest_location = lowest_location.min(location);
-        }
+    #[cfg(feature = "rayon")]
+    return part_b_parallel(almanac);
+    #[cfg(not(feature = "rayon"))]
+    part_b_serial(almanac)
+}
+
+/// Return the lowest location for any seed when the seeds are interpreted as ranges, along with
+/// the lowest seed that ends up at that location
+pub fn lowest_location_seed(almanac: &Almanac) -> (usize, usize) {
+    // Track every interval as its start, end and the seed that maps onto its start
+    let mut intervals = almanac
+        .seeds
+        .chunks(2)
+        .filter(|s| s.len() == 2 && s[1] > 0)
+        .map(|s| (s[0], s[0] + s[1], s[0]))
+        .collect::<Vec<_>>();
+    for m in almanac.maps() {
+        intervals = intervals
+            .into_iter()
+            .flat_map(|(start, end, seed)| {
+                m.split_interval((start, end)).into_iter().map(
+                    move |(src_start, src_end, dst_start)| {
+                        (
+                            dst_start,
+                            dst_start + src_end - src_start,
+                            seed + src_start - start,
+                        )
+                    },
+                )
+            })
+            .collect();
     }
-    lowest_location
+
+    intervals
+        .into_iter()
+        .map(|(location, _, seed)| (location, seed))
+        .min()
+        .unwrap_or((usize::MAX, 0))
+}
+
+/// Parse the puzzle input
+pub fn parse(input: &str) -> Result<Almanac> {
+    let almanac: Almanac = input.parse()?;
+    almanac.validate()?;
+    Ok(almanac)
+}
+
+/// Solve both parts using the parsed puzzle input
+pub fn solve(almanac: &Almanac, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part.includes_a().then(|| part_a(almanac));
+    let b = part.includes_b().then(|| part_b(almanac));
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let almanac = parse(input)?;
+    solve(&almanac, part)
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let file = std::fs::read_to_string(path)?;
-    let almanac = file.parse()?;
-    Ok((part_a(&almanac), part_b(&almanac).into()))
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
-    // Needs to be ignored because my solution is slow :(
-    test_real_input!(
-        #[ignore]
-        5,
-        111_627_841,
-        69_323_688
-    );
+    test_real_input!(5, 111_627_841, 69_323_688);
 
-    const EXAMPLE_INPUT: &'static str = concat!(
+    const EXAMPLE_INPUT: &str = concat!(
         "seeds: 79 14 55 13\n",
         "\n",
         "seed-to-soil map:\n",
@@ -228,13 +479,128 @@ mod test {
         EXAMPLE_INPUT.parse().unwrap()
     }
 
+    #[test]
+    fn test_coverage() {
+        let almanac = example_input();
+        assert_eq!(almanac.seed_to_soil.coverage(), vec![(50, 100)]);
+        assert_eq!(almanac.water_to_light.coverage(), vec![(18, 95)]);
+        assert_eq!(almanac.humidity_to_location.coverage(), vec![(56, 97)]);
+    }
+
+    #[test]
+    fn test_compose() {
+        let almanac = example_input();
+        let seed_to_location = almanac.seed_to_location_map();
+        assert_eq!(seed_to_location.map(79), 82);
+        assert_eq!(seed_to_location.map(79), almanac.seed_to_location(79));
+        for seed in 0..200 {
+            assert_eq!(seed_to_location.map(seed), almanac.seed_to_location(seed));
+        }
+
+        let seed_to_fertilizer = almanac.seed_to_soil.compose(&almanac.soil_to_fertilizer);
+        assert_eq!(seed_to_fertilizer.map(14), 53);
+    }
+
+    #[test]
+    fn test_map_ranges() {
+        let almanac = example_input();
+
+        // Seeds 79 to 92 all end up in the same soil range
+        assert_eq!(
+            almanac.seed_to_soil.map_ranges(vec![(79, 14)]),
+            vec![(81, 14)]
+        );
+
+        // The interval straddles the start of a range and the part before it is passed through
+        assert_eq!(
+            almanac.seed_to_soil.map_ranges(vec![(45, 10), (98, 4)]),
+            vec![(45, 5), (52, 5), (50, 2), (100, 2)]
+        );
+
+        // Every value is mapped the same way as when mapping them one by one
+        let mut mapped = almanac
+            .seed_to_soil
+            .map_ranges(vec![(40, 70)])
+            .into_iter()
+            .flat_map(|(start, len)| start..start + len)
+            .collect::<Vec<_>>();
+        mapped.sort();
+        let mut expected = (40..110)
+            .map(|v| almanac.seed_to_soil.map(v))
+            .collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(mapped, expected);
+    }
+
+    #[test]
+    fn test_location_to_seed() {
+        let almanac = example_input();
+        assert_eq!(almanac.seed_to_soil.unmap(81), 79);
+        assert_eq!(almanac.seed_to_soil.unmap(10), 10);
+
+        let seed = almanac.location_to_seed(46);
+        assert_eq!(seed, 82);
+        assert!(almanac
+            .seeds
+            .chunks(2)
+            .any(|s| (s[0]..s[0] + s[1]).contains(&seed)));
+        assert_eq!(almanac.seed_to_location(seed), 46);
+
+        // Every location of the seeds in part A maps back to the seed
+        for &seed in almanac.seeds.iter() {
+            assert_eq!(
+                almanac.location_to_seed(almanac.seed_to_location(seed)),
+                seed
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(example_input().validate().is_ok());
+
+        let overflowing = EXAMPLE_INPUT.replace(
+            "humidity-to-location map:\n",
+            &format!("humidity-to-location map:\n{} 0 2\n", usize::MAX - 1),
+        );
+        let almanac: Almanac = overflowing.parse().unwrap();
+        assert_eq!(
+            almanac.validate().unwrap_err().to_string(),
+            format!(
+                "Range {} 0 2 in the humidity-to-location map is out of bounds",
+                usize::MAX - 1
+            )
+        );
+        assert!(parse(&overflowing).is_err());
+
+        let overflowing_seeds =
+            EXAMPLE_INPUT.replace("seeds: 79 14", &format!("seeds: {} 14", usize::MAX));
+        assert!(parse(&overflowing_seeds).is_err());
+    }
+
     #[test]
     fn test_part_a() {
         assert_eq!(part_a(&example_input()), 35);
     }
 
+    #[test]
+    fn test_lowest_location_seed() {
+        let almanac = example_input();
+        let (location, seed) = lowest_location_seed(&almanac);
+        assert_eq!((location, seed), (46, 82));
+        assert_eq!(almanac.seed_to_location(seed), 46);
+    }
+
     #[test]
     fn test_part_b() {
         assert_eq!(part_b(&example_input()), 46);
     }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_part_b_parallel() {
+        let almanac = example_input();
+        assert_eq!(part_b_parallel(&almanac), part_b_serial(&almanac));
+        assert_eq!(part_b_parallel(&almanac), 46);
+    }
 }
@@ -1,7 +1,9 @@
+use crate::Part;
 use anyhow::{anyhow, Result};
 use std::path::Path;
 
-fn parse_races(s: &str) -> Result<Vec<(usize, usize)>> {
+/// Parse the puzzle input
+pub fn parse(s: &str) -> Result<Vec<(usize, usize)>> {
     let mut lines = s.lines();
     let Some(time_str) = lines.next().filter(|l| l.starts_with("Time: ")) else {
         return Err(anyhow!("Unable to find times"));
@@ -24,13 +26,29 @@ fn parse_races(s: &str) -> Result<Vec<(usize, usize)>> {
 }
 
 fn num_winning_ways(time: usize, distance_to_beat: usize) -> usize {
-    let mut num_ways = 0;
-    for hold_time in 1..time {
-        if hold_time * (time - hold_time) > distance_to_beat {
-            num_ways += 1;
-        }
+    let (time, distance) = (time as u128, distance_to_beat as u128);
+    let wins = |hold_time: u128| hold_time * (time - hold_time) > distance;
+
+    // The winning hold times lie strictly between the roots of hold^2 - time*hold + distance = 0
+    let Some(discriminant) = (time * time).checked_sub(4 * distance) else {
+        return 0;
+    };
+
+    // The integer square root may be off by one from the real root, so nudge the estimate of the
+    // first winning hold time until it's exact
+    let mut first = (time - discriminant.isqrt()) / 2;
+    while first > 0 && wins(first - 1) {
+        first -= 1;
+    }
+    while first <= time / 2 && !wins(first) {
+        first += 1;
+    }
+    if first > time / 2 {
+        return 0;
     }
-    num_ways
+
+    // Winning hold times are symmetric around time / 2
+    usize::try_from(time - 2 * first + 1).unwrap()
 }
 
 fn concat_usize(nums: impl Iterator<Item = usize>) -> usize {
@@ -39,6 +57,26 @@ fn concat_usize(nums: impl Iterator<Item = usize>) -> usize {
     })
 }
 
+/// Parse races where every `group` consecutive numbers are concatenated into a single number. A
+/// group size of one gives the races of part A, while a group as large as the number of races
+/// gives the single race of part B
+pub fn parse_with_group_size(s: &str, group: usize) -> Result<Vec<(usize, usize)>> {
+    if group == 0 {
+        return Err(anyhow!("Group size must be at least one"));
+    }
+
+    let races = parse(s)?;
+    Ok(races
+        .chunks(group)
+        .map(|chunk| {
+            (
+                concat_usize(chunk.iter().map(|(t, _)| *t)),
+                concat_usize(chunk.iter().map(|(_, d)| *d)),
+            )
+        })
+        .collect())
+}
+
 fn part_a(races: &[(usize, usize)]) -> usize {
     races
         .iter()
@@ -53,11 +91,20 @@ fn part_b(races: &[(usize, usize)]) -> usize {
     num_winning_ways(time, distance)
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let file = std::fs::read_to_string(path)?;
-    let races = parse_races(&file)?;
+/// Solve both parts using the parsed puzzle input
+pub fn solve(races: &[(usize, usize)], part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part.includes_a().then(|| part_a(races));
+    let b = part.includes_b().then(|| part_b(races));
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let races = parse(input)?;
+    solve(&races, part)
+}
 
-    Ok((part_a(&races), part_b(&races).into()))
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -66,11 +113,10 @@ mod test {
 
     test_real_input!(6, 1_710_720, 35_349_468);
 
-    const EXAMPLE_INPUT: &'static str =
-        concat!("Time:      7  15   30\n", "Distance:  9  40  200\n",);
+    const EXAMPLE_INPUT: &str = concat!("Time:      7  15   30\n", "Distance:  9  40  200\n",);
 
     fn example_input() -> Vec<(usize, usize)> {
-        parse_races(EXAMPLE_INPUT).unwrap()
+        parse(EXAMPLE_INPUT).unwrap()
     }
 
     #[test]
@@ -80,6 +126,34 @@ mod test {
         assert_eq!(num_winning_ways(30, 200), 9);
     }
 
+    #[test]
+    fn test_num_winning_ways_matches_brute_force() {
+        let brute_force = |time: usize, distance: usize| {
+            (1..time)
+                .filter(|hold_time| hold_time * (time - hold_time) > distance)
+                .count()
+        };
+        for time in 0..40 {
+            for distance in 0..400 {
+                assert_eq!(
+                    num_winning_ways(time, distance),
+                    brute_force(time, distance)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_num_winning_ways_large() {
+        assert_eq!(num_winning_ways(71530, 940200), 71503);
+
+        // The product is exactly the distance at hold times 10^7 and 5 * 10^7
+        assert_eq!(
+            num_winning_ways(60_000_000, 500_000_000_000_000),
+            39_999_999
+        );
+    }
+
     #[test]
     fn test_concat_usize() {
         assert_eq!(concat_usize([1, 10, 100].into_iter()), 110100);
@@ -87,6 +161,23 @@ mod test {
         assert_eq!(concat_usize([123, 45, 6789].into_iter()), 123456789);
     }
 
+    #[test]
+    fn test_parse_with_group_size() {
+        assert_eq!(
+            parse_with_group_size(EXAMPLE_INPUT, 1).unwrap(),
+            example_input()
+        );
+        assert_eq!(
+            parse_with_group_size(EXAMPLE_INPUT, 2).unwrap(),
+            vec![(715, 940), (30, 200)]
+        );
+        assert_eq!(
+            parse_with_group_size(EXAMPLE_INPUT, 3).unwrap(),
+            vec![(71530, 940200)]
+        );
+        assert!(parse_with_group_size(EXAMPLE_INPUT, 0).is_err());
+    }
+
     #[test]
     fn test_part_a() {
         assert_eq!(part_a(&example_input()), 288);
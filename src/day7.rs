@@ -1,14 +1,27 @@
+use crate::Part;
 use anyhow::{anyhow, Result};
+use std::cmp::{Ordering, Reverse};
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::hash::Hash;
 use std::path::Path;
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord)]
-struct Card(usize);
+pub struct Card(usize);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Hand([Card; 5]);
+pub struct Hand([Card; 5]);
+
+/// The type of a hand, ordered from weakest to strongest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HandType {
+    HighCard,
+    OnePair,
+    TwoPairs,
+    ThreeOfAKind,
+    FullHouse,
+    FourOfAKind,
+    FiveOfAKind,
+}
 
 impl Card {
     fn from_char(c: char) -> Option<Self> {
@@ -32,15 +45,24 @@ impl Card {
     }
 }
 
-impl Hand {
-    fn count_cards(&self) -> HashMap<Card, usize> {
-        let mut counts = HashMap::new();
-        for card in self.0.iter().copied() {
-            *counts.entry(card).or_default() += 1;
-        }
-        counts
+/// Count how many times each distinct item occurs, from the most common to the least common.
+/// Items that occur equally often are kept in the order they first appeared
+pub fn counts_sorted_desc<T: Eq + Hash>(items: impl IntoIterator<Item = T>) -> Vec<(T, usize)> {
+    // Remember where every item first occurred so ties are broken consistently
+    let mut counts: HashMap<T, (usize, usize)> = HashMap::new();
+    for (i, item) in items.into_iter().enumerate() {
+        counts.entry(item).or_insert((i, 0)).1 += 1;
     }
 
+    let mut sorted_counts = counts.into_iter().collect::<Vec<_>>();
+    sorted_counts.sort_by_key(|&(_, (first_seen, count))| (Reverse(count), first_seen));
+    sorted_counts
+        .into_iter()
+        .map(|(item, (_, count))| (item, count))
+        .collect()
+}
+
+impl Hand {
     fn jacks_into_jokers(&self) -> Self {
         Self([
             self.0[0].jack_into_joker(),
@@ -51,14 +73,31 @@ impl Hand {
         ])
     }
 
-    fn tier(&self) -> usize {
-        let mut card_counts = self.count_cards();
-        let num_jokers = card_counts.remove(&Card(1)).unwrap_or(0);
+    /// Return how many of each card the hand has, from the most common to the least common
+    pub fn composition(&self) -> Vec<usize> {
+        counts_sorted_desc(self.0)
+            .into_iter()
+            .map(|(_, count)| count)
+            .collect()
+    }
+
+    /// Return the type of the hand. When jokers are enabled jacks are assigned to whatever card
+    /// that makes the hand as strong as possible
+    pub fn effective_type(&self, jokers: bool) -> HandType {
+        if jokers {
+            self.jacks_into_jokers().hand_type()
+        } else {
+            self.hand_type()
+        }
+    }
 
-        // Sort the card counts in reverse order
-        let mut sorted_card_counts = card_counts.values().copied().collect::<Vec<_>>();
-        sorted_card_counts.sort();
-        sorted_card_counts.reverse();
+    fn hand_type(&self) -> HandType {
+        let is_joker = |card: &Card| *card == Card(1);
+        let num_jokers = self.0.iter().filter(|c| is_joker(c)).count();
+        let sorted_card_counts = counts_sorted_desc(self.0.into_iter().filter(|c| !is_joker(c)))
+            .into_iter()
+            .map(|(_, count)| count)
+            .collect::<Vec<_>>();
 
         // We only need to look at the first two values to determine the hand. If we have jokers we
         // we can simply add them to the most common card since that will always net us the best
@@ -67,18 +106,31 @@ impl Hand {
         let b = sorted_card_counts.get(1).copied().unwrap_or(0);
 
         match (a, b) {
-            (5, 0) => 6, // Five of a kind
-            (4, 1) => 5, // Four of a kind
-            (3, 2) => 4, // Full house
-            (3, 1) => 3, // Three of a kind
-            (2, 2) => 2, // Two pairs
-            (2, 1) => 1, // One pair
-            (1, 1) => 0, // All unique
+            (5, 0) => HandType::FiveOfAKind,
+            (4, 1) => HandType::FourOfAKind,
+            (3, 2) => HandType::FullHouse,
+            (3, 1) => HandType::ThreeOfAKind,
+            (2, 2) => HandType::TwoPairs,
+            (2, 1) => HandType::OnePair,
+            (1, 1) => HandType::HighCard,
             _ => panic!("We should never get here"),
         }
     }
 }
 
+/// Hands are ordered by type and then card by card, without treating jacks as jokers
+impl Ord for Hand {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp_hands(self, other, false)
+    }
+}
+
+impl PartialOrd for Hand {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 fn parse_hand_with_bid(s: &str) -> Result<(Hand, usize)> {
     let Some((hand_str, bid_str)) = s.split_once(' ') else {
         return Err(anyhow!("Unable to find bid in {:?}", s));
@@ -95,17 +147,21 @@ fn parse_hand_with_bid(s: &str) -> Result<(Hand, usize)> {
     ))
 }
 
-fn total_winnings(hands: &[(Hand, usize)], jacks_into_jokers: bool) -> usize {
-    let mut sorted_hands = if jacks_into_jokers {
-        hands
-            .iter()
-            .cloned()
-            .map(|(hand, bid)| (hand.jacks_into_jokers(), bid))
-            .collect::<Vec<_>>()
+/// Compare hands by type first and then card by card from left to right. When jokers are enabled
+/// jacks are treated as jokers, both when determining the type and when comparing cards
+pub fn cmp_hands(a: &Hand, b: &Hand, jokers: bool) -> Ordering {
+    let (a_type, b_type) = (a.effective_type(jokers), b.effective_type(jokers));
+    let (a, b) = if jokers {
+        (a.jacks_into_jokers(), b.jacks_into_jokers())
     } else {
-        hands.to_vec()
+        (*a, *b)
     };
-    sorted_hands.sort_by_key(|(hand, _)| (hand.tier(), hand.0));
+    (a_type, a.0).cmp(&(b_type, b.0))
+}
+
+fn total_winnings(hands: &[(Hand, usize)], jacks_into_jokers: bool) -> usize {
+    let mut sorted_hands = hands.to_vec();
+    sorted_hands.sort_by(|(a, _), (b, _)| cmp_hands(a, b, jacks_into_jokers));
 
     let mut winnings = 0;
     for (i, (_, bid)) in sorted_hands.into_iter().enumerate() {
@@ -114,16 +170,25 @@ fn total_winnings(hands: &[(Hand, usize)], jacks_into_jokers: bool) -> usize {
     winnings
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let file = File::open(path)?;
-    let hands = BufReader::new(file)
-        .lines()
-        .map(|l| parse_hand_with_bid(&l?))
-        .collect::<Result<Vec<(Hand, usize)>, _>>()?;
-    Ok((
-        total_winnings(&hands, false),
-        total_winnings(&hands, true).into(),
-    ))
+/// Parse the puzzle input
+pub fn parse(s: &str) -> Result<Vec<(Hand, usize)>> {
+    s.lines().map(parse_hand_with_bid).collect()
+}
+
+/// Solve both parts using the parsed puzzle input
+pub fn solve(hands: &[(Hand, usize)], part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part.includes_a().then(|| total_winnings(hands, false));
+    let b = part.includes_b().then(|| total_winnings(hands, true));
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let hands = parse(input)?;
+    solve(&hands, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -133,14 +198,80 @@ mod test {
     test_real_input!(7, 250_946_742, 251_824_095);
 
     #[test]
-    fn test_hand_tier() {
-        assert_eq!(parse_hand_with_bid("2468T 0").unwrap().0.tier(), 0);
-        assert_eq!(parse_hand_with_bid("24682 0").unwrap().0.tier(), 1);
-        assert_eq!(parse_hand_with_bid("24642 0").unwrap().0.tier(), 2);
-        assert_eq!(parse_hand_with_bid("22682 0").unwrap().0.tier(), 3);
-        assert_eq!(parse_hand_with_bid("22662 0").unwrap().0.tier(), 4);
-        assert_eq!(parse_hand_with_bid("22622 0").unwrap().0.tier(), 5);
-        assert_eq!(parse_hand_with_bid("22222 0").unwrap().0.tier(), 6);
+    fn test_hand_type() {
+        assert_eq!(
+            parse_hand_with_bid("2468T 0").unwrap().0.hand_type(),
+            HandType::HighCard
+        );
+        assert_eq!(
+            parse_hand_with_bid("24682 0").unwrap().0.hand_type(),
+            HandType::OnePair
+        );
+        assert_eq!(
+            parse_hand_with_bid("24642 0").unwrap().0.hand_type(),
+            HandType::TwoPairs
+        );
+        assert_eq!(
+            parse_hand_with_bid("22682 0").unwrap().0.hand_type(),
+            HandType::ThreeOfAKind
+        );
+        assert_eq!(
+            parse_hand_with_bid("22662 0").unwrap().0.hand_type(),
+            HandType::FullHouse
+        );
+        assert_eq!(
+            parse_hand_with_bid("22622 0").unwrap().0.hand_type(),
+            HandType::FourOfAKind
+        );
+        assert_eq!(
+            parse_hand_with_bid("22222 0").unwrap().0.hand_type(),
+            HandType::FiveOfAKind
+        );
+    }
+
+    #[test]
+    fn test_composition() {
+        let composition = |s| parse_hand_with_bid(s).unwrap().0.composition();
+        assert_eq!(composition("22662 0"), vec![3, 2]);
+        assert_eq!(composition("2468T 0"), vec![1, 1, 1, 1, 1]);
+        assert_eq!(composition("T55J5 0"), vec![3, 1, 1]);
+    }
+
+    #[test]
+    fn test_counts_sorted_desc() {
+        assert_eq!(
+            counts_sorted_desc("AAABB".chars()),
+            vec![('A', 3), ('B', 2)]
+        );
+        assert_eq!(
+            counts_sorted_desc("ABBAC".chars()),
+            vec![('A', 2), ('B', 2), ('C', 1)]
+        );
+        assert_eq!(counts_sorted_desc(Vec::<char>::new()), vec![]);
+    }
+
+    #[test]
+    fn test_effective_type() {
+        let hand = parse_hand_with_bid("T55J5 0").unwrap().0;
+        assert_eq!(hand.effective_type(false), HandType::ThreeOfAKind);
+        assert_eq!(hand.effective_type(true), HandType::FourOfAKind);
+    }
+
+    #[test]
+    fn test_cmp_hands() {
+        let kings = parse_hand_with_bid("KKKQQ 0").unwrap().0;
+        let queens = parse_hand_with_bid("QKKQQ 0").unwrap().0;
+        let jacks = parse_hand_with_bid("JKKQQ 0").unwrap().0;
+
+        // Both are full houses, so the first card decides
+        assert_eq!(cmp_hands(&kings, &queens, false), Ordering::Greater);
+        assert_eq!(cmp_hands(&queens, &kings, false), Ordering::Less);
+        assert_eq!(cmp_hands(&kings, &kings, false), Ordering::Equal);
+
+        // A joker makes a full house as well, but is the weakest card when breaking ties
+        assert_eq!(cmp_hands(&jacks, &queens, false), Ordering::Less);
+        assert_eq!(cmp_hands(&jacks, &queens, true), Ordering::Less);
+        assert_eq!(cmp_hands(&jacks, &kings, true), Ordering::Less);
     }
 
     fn example_input() -> Vec<(Hand, usize)> {
@@ -153,6 +284,20 @@ mod test {
         ]
     }
 
+    #[test]
+    fn test_ord() {
+        let mut hands = example_input()
+            .into_iter()
+            .map(|(hand, _)| hand)
+            .collect::<Vec<_>>();
+        hands.sort();
+
+        let expected = ["32T3K", "KTJJT", "KK677", "T55J5", "QQQJA"]
+            .map(|s| parse_hand_with_bid(&format!("{} 0", s)).unwrap().0);
+        assert_eq!(hands, expected);
+        assert!(hands.windows(2).all(|w| w[0] < w[1]));
+    }
+
     #[test]
     fn test_part_a() {
         assert_eq!(total_winnings(&example_input(), false), 6440);
@@ -1,25 +1,18 @@
+use crate::math::lcm;
+use crate::Part;
 use anyhow::{anyhow, Result};
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
-enum LR {
+#[derive(Debug)]
+pub enum LR {
     Left,
     Right,
 }
 
-fn gcd(mut a: usize, mut b: usize) -> usize {
-    while b != 0 {
-        (a, b) = (b, a % b);
-    }
-    a
-}
-
-fn lcm(a: usize, b: usize) -> usize {
-    (a * b) / gcd(a, b)
-}
-
+/// Parse the puzzle input
 #[allow(clippy::type_complexity)]
-fn parse_input(s: &str) -> Result<(Vec<LR>, HashMap<String, (String, String)>)> {
+pub fn parse(s: &str) -> Result<(Vec<LR>, HashMap<String, (String, String)>)> {
     let Some((lr_str, map_str)) = s.split_once("\n\n") else {
         return Err(anyhow!("Can't find two separate blocks in map"));
     };
@@ -81,8 +74,12 @@ fn follow_steps(
     unreachable!();
 }
 
-fn follow_ghost_steps(steps: &[LR], map: &HashMap<String, (String, String)>) -> Result<usize> {
-    let mut a_to_z_steps: HashMap<(&str, &str), usize> = HashMap::new();
+/// Return the number of steps from every ??A node to each ??Z node it can reach
+fn ghost_offsets<'a>(
+    steps: &[LR],
+    map: &'a HashMap<String, (String, String)>,
+) -> Result<HashMap<(&'a str, &'a str), usize>> {
+    let mut a_to_z_steps = HashMap::new();
     for src in map.keys() {
         if !src.ends_with('A') {
             continue;
@@ -94,16 +91,58 @@ fn follow_ghost_steps(steps: &[LR], map: &HashMap<String, (String, String)>) ->
             let Some(num_steps) = follow_steps(steps, map, src, dst)? else {
                 continue;
             };
-            a_to_z_steps.insert((src, dst), num_steps);
+            a_to_z_steps.insert((src.as_str(), dst.as_str()), num_steps);
         }
     }
+    Ok(a_to_z_steps)
+}
 
-    let mut running_lcm = 1;
-    let mut steps_until_aligned = 1;
-    for ((_, from_to), offset) in a_to_z_steps.iter() {
-        let Some(steps_in_cycle) = follow_steps(steps, map, from_to, from_to)? else {
+/// Return the number of steps it takes to get from the ??Z node of every ??A to ??Z pair back to
+/// the same ??Z node
+fn destination_cycles<'a>(
+    steps: &[LR],
+    map: &HashMap<String, (String, String)>,
+    offsets: &HashMap<(&'a str, &'a str), usize>,
+) -> Result<HashMap<(&'a str, &'a str), usize>> {
+    let mut cycles = HashMap::new();
+    for &(src, dst) in offsets.keys() {
+        let Some(steps_in_cycle) = follow_steps(steps, map, dst, dst)? else {
             return Err(anyhow!("We expect ??Z to ??Z always have a valid path"));
         };
+        cycles.insert((src, dst), steps_in_cycle);
+    }
+    Ok(cycles)
+}
+
+/// Return the number of steps it takes the ghost starting at each ??A node to get from its ??Z
+/// node back to the same ??Z node. If a ghost can reach several ??Z nodes, the cycle of the one it
+/// reaches first is used
+pub fn ghost_cycles(
+    steps: &[LR],
+    map: &HashMap<String, (String, String)>,
+) -> Result<HashMap<String, usize>> {
+    let offsets = ghost_offsets(steps, map)?;
+    let mut first_dst = HashMap::new();
+    for (&(src, dst), &offset) in offsets.iter() {
+        let first = first_dst.entry(src).or_insert((offset, dst));
+        *first = (*first).min((offset, dst));
+    }
+
+    let cycles = destination_cycles(steps, map, &offsets)?;
+    Ok(first_dst
+        .into_iter()
+        .map(|(src, (_, dst))| (src.to_string(), cycles[&(src, dst)]))
+        .collect())
+}
+
+fn follow_ghost_steps(steps: &[LR], map: &HashMap<String, (String, String)>) -> Result<usize> {
+    let offsets = ghost_offsets(steps, map)?;
+    let cycles = destination_cycles(steps, map, &offsets)?;
+
+    let mut running_lcm = 1;
+    let mut steps_until_aligned = 1;
+    for (src_dst, offset) in offsets {
+        let steps_in_cycle = cycles[&src_dst];
         while (steps_until_aligned + offset) % steps_in_cycle != 0 {
             steps_until_aligned += running_lcm;
         }
@@ -112,13 +151,33 @@ fn follow_ghost_steps(steps: &[LR], map: &HashMap<String, (String, String)>) ->
     Ok(steps_until_aligned)
 }
 
-pub fn main(path: &Path) -> Result<(usize, Option<usize>)> {
-    let file = std::fs::read_to_string(path)?;
-    let (steps, map) = parse_input(&file)?;
-    Ok((
-        follow_steps(&steps, &map, "AAA", "ZZZ")?.unwrap(),
-        follow_ghost_steps(&steps, &map)?.into(),
-    ))
+/// Solve both parts using the parsed puzzle input
+#[allow(clippy::type_complexity)]
+pub fn solve(
+    (steps, map): &(Vec<LR>, HashMap<String, (String, String)>),
+    part: Part,
+) -> Result<(Option<usize>, Option<usize>)> {
+    let a = part
+        .includes_a()
+        .then(|| {
+            follow_steps(steps, map, "AAA", "ZZZ")?
+                .ok_or_else(|| anyhow!("No path from AAA to ZZZ"))
+        })
+        .transpose()?;
+    let b = part
+        .includes_b()
+        .then(|| follow_ghost_steps(steps, map))
+        .transpose()?;
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<usize>, Option<usize>)> {
+    let parsed = parse(input)?;
+    solve(&parsed, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<usize>, Option<usize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -140,28 +199,56 @@ mod test {
             "GGG = (GGG, GGG)\n",
             "ZZZ = (ZZZ, ZZZ)\n",
         );
-        let (steps, map) = parse_input(input).unwrap();
+        let (steps, map) = parse(input).unwrap();
         assert_eq!(
             follow_steps(&steps, &map, "AAA", "ZZZ").unwrap().unwrap(),
             2
         );
     }
 
+    const EXAMPLE_INPUT_B: &str = concat!(
+        "LR\n",
+        "\n",
+        "11A = (11B, XXX)\n",
+        "11B = (XXX, 11Z)\n",
+        "11Z = (11B, XXX)\n",
+        "22A = (22B, XXX)\n",
+        "22B = (22C, 22C)\n",
+        "22C = (22Z, 22Z)\n",
+        "22Z = (22B, 22B)\n",
+        "XXX = (XXX, XXX)\n",
+    );
+
     #[test]
     fn test_part_b() {
-        let input = concat!(
-            "LR\n",
+        let (steps, map) = parse(EXAMPLE_INPUT_B).unwrap();
+        assert_eq!(follow_ghost_steps(&steps, &map).unwrap(), 6);
+    }
+
+    #[test]
+    fn test_ghost_cycles() {
+        let (steps, map) = parse(EXAMPLE_INPUT_B).unwrap();
+        assert_eq!(
+            ghost_cycles(&steps, &map).unwrap(),
+            HashMap::from([("11A".to_string(), 2), ("22A".to_string(), 3)])
+        );
+
+        // The ghost reaches both 11Z and 22Z, so the cycle of 11Z is reported since it comes first
+        let (steps, map) = parse(concat!(
+            "L\n",
             "\n",
-            "11A = (11B, XXX)\n",
-            "11B = (XXX, 11Z)\n",
-            "11Z = (11B, XXX)\n",
-            "22A = (22B, XXX)\n",
-            "22B = (22C, 22C)\n",
-            "22C = (22Z, 22Z)\n",
-            "22Z = (22B, 22B)\n",
+            "11A = (11Z, XXX)\n",
+            "11Z = (22Z, XXX)\n",
+            "22Z = (11Z, XXX)\n",
             "XXX = (XXX, XXX)\n",
+        ))
+        .unwrap();
+        let offsets = ghost_offsets(&steps, &map).unwrap();
+        assert_eq!(offsets[&("11A", "11Z")], 1);
+        assert_eq!(offsets[&("11A", "22Z")], 2);
+        assert_eq!(
+            ghost_cycles(&steps, &map).unwrap(),
+            HashMap::from([("11A".to_string(), 2)])
         );
-        let (steps, map) = parse_input(input).unwrap();
-        assert_eq!(follow_ghost_steps(&steps, &map).unwrap(), 6);
     }
 }
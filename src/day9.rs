@@ -1,20 +1,33 @@
+use crate::Part;
 use anyhow::{anyhow, Result};
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 use std::path::Path;
 
 fn iter_pairs<T: Clone>(seq: &[T]) -> impl Iterator<Item = (T, T)> + '_ {
     seq.iter().cloned().zip(seq.iter().skip(1).cloned())
 }
 
-fn next_value(seq: &[isize]) -> Option<isize> {
+/// Return the sequence followed by rows of differences until a row of identical values is reached
+fn difference_pyramid(seq: &[isize]) -> Option<Vec<Vec<isize>>> {
     let mut stack: Vec<Vec<isize>> = Vec::new();
     stack.push(seq.to_vec());
 
     while iter_pairs(stack.last()?).any(|(a, b)| a != b) {
         stack.push(iter_pairs(stack.last()?).map(|(a, b)| b - a).collect());
     }
+    Some(stack)
+}
+
+/// Return the number of difference rows required to reach a constant row, which is the degree of
+/// the polynomial generating the sequence
+pub fn sequence_degree(seq: &[isize]) -> Option<usize> {
+    if seq.is_empty() {
+        return None;
+    }
+    Some(difference_pyramid(seq)?.len() - 1)
+}
 
+fn next_value(seq: &[isize]) -> Option<isize> {
+    let mut stack = difference_pyramid(seq)?;
     while stack.len() > 1 {
         let diff = *stack.pop()?.last()?;
         let parent = stack.last_mut()?;
@@ -59,13 +72,25 @@ fn parse_seq(s: &str) -> Result<Vec<isize>> {
         .collect::<Result<Vec<_>, _>>()
 }
 
-pub fn main(path: &Path) -> Result<(isize, Option<isize>)> {
-    let file = File::open(path)?;
-    let seqs = BufReader::new(file)
-        .lines()
-        .map(|l| parse_seq(&l?))
-        .collect::<Result<Vec<Vec<isize>>, _>>()?;
-    Ok((part_a(&seqs)?, part_b(&seqs)?.into()))
+/// Parse the puzzle input
+pub fn parse(s: &str) -> Result<Vec<Vec<isize>>> {
+    s.lines().map(parse_seq).collect()
+}
+
+/// Solve both parts using the parsed puzzle input
+pub fn solve(seqs: &[Vec<isize>], part: Part) -> Result<(Option<isize>, Option<isize>)> {
+    let a = part.includes_a().then(|| part_a(seqs)).transpose()?;
+    let b = part.includes_b().then(|| part_b(seqs)).transpose()?;
+    Ok((a, b))
+}
+
+pub fn run(input: &str, part: Part) -> Result<(Option<isize>, Option<isize>)> {
+    let seqs = parse(input)?;
+    solve(&seqs, part)
+}
+
+pub fn main(path: &Path) -> Result<(Option<isize>, Option<isize>)> {
+    run(&std::fs::read_to_string(path)?, Part::Both)
 }
 
 #[cfg(test)]
@@ -74,9 +99,9 @@ mod test {
 
     test_real_input!(9, 1_731_106_378, 1087);
 
-    const A: &'static [isize] = &[0, 3, 6, 9, 12, 15];
-    const B: &'static [isize] = &[1, 3, 6, 10, 15, 21];
-    const C: &'static [isize] = &[10, 13, 16, 21, 30, 45];
+    const A: &[isize] = &[0, 3, 6, 9, 12, 15];
+    const B: &[isize] = &[1, 3, 6, 10, 15, 21];
+    const C: &[isize] = &[10, 13, 16, 21, 30, 45];
 
     #[test]
     fn test_next_digit() {
@@ -85,6 +110,15 @@ mod test {
         assert_eq!(next_value(C), Some(68));
     }
 
+    #[test]
+    fn test_sequence_degree() {
+        assert_eq!(sequence_degree(&[0, 3, 6, 9]), Some(1));
+        assert_eq!(sequence_degree(&[1, 3, 6, 10]), Some(2));
+        assert_eq!(sequence_degree(C), Some(3));
+        assert_eq!(sequence_degree(&[5, 5, 5]), Some(0));
+        assert_eq!(sequence_degree(&[]), None);
+    }
+
     #[test]
     fn test_part_a() {
         assert_eq!(part_a(&[A.to_vec(), B.to_vec(), C.to_vec()]).unwrap(), 114);
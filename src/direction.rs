@@ -0,0 +1,141 @@
+use anyhow::{anyhow, Result};
+use std::fmt;
+
+/// One of the four directions on a grid where up is towards lower y values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// Parse one of the letters `U`, `D`, `L` or `R`
+    pub fn from_letter(c: char) -> Result<Self> {
+        match c {
+            'U' => Ok(Self::Up),
+            'D' => Ok(Self::Down),
+            'L' => Ok(Self::Left),
+            'R' => Ok(Self::Right),
+            _ => Err(anyhow!("Unexpected direction {:?}", c)),
+        }
+    }
+
+    /// Parse one of the arrows `^`, `v`, `<` or `>`
+    pub fn from_arrow(c: char) -> Result<Self> {
+        match c {
+            '^' => Ok(Self::Up),
+            'v' => Ok(Self::Down),
+            '<' => Ok(Self::Left),
+            '>' => Ok(Self::Right),
+            _ => Err(anyhow!("Unexpected arrow {:?}", c)),
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+
+    pub fn turn_left(self) -> Self {
+        match self {
+            Self::Up => Self::Left,
+            Self::Down => Self::Right,
+            Self::Left => Self::Down,
+            Self::Right => Self::Up,
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        match self {
+            Self::Up => Self::Right,
+            Self::Down => Self::Left,
+            Self::Left => Self::Up,
+            Self::Right => Self::Down,
+        }
+    }
+
+    /// Return the position one step away from the given one in this direction
+    pub fn step(self, (x, y): (isize, isize)) -> (isize, isize) {
+        match self {
+            Self::Up => (x, y - 1),
+            Self::Down => (x, y + 1),
+            Self::Left => (x - 1, y),
+            Self::Right => (x + 1, y),
+        }
+    }
+
+    /// Return the position one step away from the given one in this direction, unless that would
+    /// step past zero
+    pub fn checked_step(self, (x, y): (usize, usize)) -> Option<(usize, usize)> {
+        match self {
+            Self::Up => Some((x, y.checked_sub(1)?)),
+            Self::Down => Some((x, y + 1)),
+            Self::Left => Some((x.checked_sub(1)?, y)),
+            Self::Right => Some((x + 1, y)),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Up => "Up",
+            Self::Down => "Down",
+            Self::Left => "Left",
+            Self::Right => "Right",
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Direction::Left.to_string(), "Left");
+        assert_eq!(format!("{}", Direction::Up), "Up");
+    }
+
+    #[test]
+    fn test_parse() {
+        for (letter, arrow, dir) in [
+            ('U', '^', Direction::Up),
+            ('D', 'v', Direction::Down),
+            ('L', '<', Direction::Left),
+            ('R', '>', Direction::Right),
+        ] {
+            assert_eq!(Direction::from_letter(letter).unwrap(), dir);
+            assert_eq!(Direction::from_arrow(arrow).unwrap(), dir);
+        }
+        assert!(Direction::from_letter('^').is_err());
+        assert!(Direction::from_arrow('U').is_err());
+    }
+
+    #[test]
+    fn test_turns() {
+        for dir in [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ] {
+            assert_eq!(dir.turn_left().turn_right(), dir);
+            assert_eq!(dir.turn_left().turn_left(), dir.opposite());
+            assert_eq!(dir.opposite().opposite(), dir);
+            assert_eq!(dir.opposite().step(dir.step((3, -2))), (3, -2));
+        }
+        assert_eq!(Direction::Up.turn_right(), Direction::Right);
+        assert_eq!(Direction::Up.step((0, 0)), (0, -1));
+        assert_eq!(Direction::Up.checked_step((0, 0)), None);
+        assert_eq!(Direction::Left.checked_step((0, 3)), None);
+        assert_eq!(Direction::Right.checked_step((0, 3)), Some((1, 3)));
+    }
+}
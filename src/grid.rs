@@ -0,0 +1,290 @@
+use anyhow::{anyhow, Result};
+use std::collections::{HashMap, HashSet};
+
+/// A rectangular 2D grid of cells stored in row major order
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Parse a grid of characters where every line is a row, converting each character using the
+    /// given function
+    pub fn from_str_with(s: &str, f: impl Fn(char) -> Result<T>) -> Result<Self> {
+        let mut width = 0;
+        let mut height = 0;
+        let mut cells = Vec::new();
+        for (y, line) in s.lines().enumerate() {
+            let len_before = cells.len();
+            for c in line.chars() {
+                cells.push(f(c)?);
+            }
+
+            let row_width = cells.len() - len_before;
+            if y == 0 {
+                width = row_width;
+            } else if row_width != width {
+                return Err(anyhow!(
+                    "Row {} has width {}, expected {}",
+                    y,
+                    row_width,
+                    width
+                ));
+            }
+            height = y + 1;
+        }
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if x < self.width && y < self.height {
+            self.cells.get(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        if x < self.width && y < self.height {
+            self.cells.get_mut(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// Iterate over every cell along with its coordinate, row by row
+    pub fn iter_coords(&self) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| ((i % self.width, i / self.width), cell))
+    }
+
+    /// Return a grid of the same size where every cell is converted using the given function
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Return all cells within the grid that share an edge with the given cell
+    pub fn neighbors4(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        [
+            y.checked_sub(1).map(|ny| (x, ny)),
+            Some((x, y + 1)),
+            x.checked_sub(1).map(|nx| (nx, y)),
+            Some((x + 1, y)),
+        ]
+        .into_iter()
+        .flatten()
+        .filter(|&(nx, ny)| nx < self.width && ny < self.height)
+    }
+
+    /// Return all cells within the grid that are adjacent to the given cell, including diagonals
+    pub fn neighbors8(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (y.saturating_sub(1)..=(y + 1)).flat_map(move |ny| {
+            (x.saturating_sub(1)..=(x + 1)).filter_map(move |nx| {
+                let is_self = x == nx && y == ny;
+                (!is_self && nx < self.width && ny < self.height).then_some((nx, ny))
+            })
+        })
+    }
+}
+
+/// Parse a grid of characters into its width, height and a map of all non-empty cells. The given
+/// function converts each character and returns `None` for empty cells
+#[allow(clippy::type_complexity)]
+pub fn parse_sparse<F, T>(s: &str, f: F) -> Result<(usize, usize, HashMap<(usize, usize), T>)>
+where
+    F: Fn(char) -> Result<Option<T>>,
+{
+    let mut width = 0;
+    let mut height = 0;
+    let mut cells = HashMap::new();
+    for (y, line) in s.lines().enumerate() {
+        for (x, c) in line.chars().enumerate() {
+            if let Some(cell) = f(c)? {
+                cells.insert((x, y), cell);
+            }
+            width = width.max(x + 1);
+        }
+        height = y + 1;
+    }
+    Ok((width, height, cells))
+}
+
+/// Return every cell reachable from the start by moving up, down, left or right through passable
+/// cells, including the start itself. When bounds are given as inclusive top left and bottom right
+/// corners, the fill never leaves them. Without bounds the passable region must be finite
+pub fn flood_fill(
+    start: (isize, isize),
+    bounds: Option<((isize, isize), (isize, isize))>,
+    passable: impl Fn((isize, isize)) -> bool,
+) -> HashSet<(isize, isize)> {
+    let in_bounds = |(x, y): (isize, isize)| {
+        bounds.is_none_or(|((min_x, min_y), (max_x, max_y))| {
+            (min_x..=max_x).contains(&x) && (min_y..=max_y).contains(&y)
+        })
+    };
+
+    let mut filled = HashSet::new();
+    let mut to_visit = vec![start];
+    while let Some((x, y)) = to_visit.pop() {
+        if !in_bounds((x, y)) || !passable((x, y)) || !filled.insert((x, y)) {
+            continue;
+        }
+        to_visit.extend([(x, y - 1), (x, y + 1), (x - 1, y), (x + 1, y)]);
+    }
+    filled
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn example_grid() -> Grid<char> {
+        Grid::from_str_with("abc\ndef\nghi\n", Ok).unwrap()
+    }
+
+    #[test]
+    fn test_from_str_with() {
+        let grid = example_grid();
+        assert_eq!((grid.width(), grid.height()), (3, 3));
+        assert_eq!(grid.get(0, 0), Some(&'a'));
+        assert_eq!(grid.get(2, 1), Some(&'f'));
+        assert_eq!(grid.get(3, 1), None);
+        assert!(Grid::from_str_with("ab\nc\n", Ok).is_err());
+    }
+
+    #[test]
+    fn test_iter_coords() {
+        let grid = example_grid();
+        let cells = grid.iter_coords().collect::<Vec<_>>();
+        assert_eq!(cells.len(), 9);
+        assert_eq!(cells[0], ((0, 0), &'a'));
+        assert_eq!(cells[5], ((2, 1), &'f'));
+        for (x, y) in (0..3).flat_map(|y| (0..3).map(move |x| (x, y))) {
+            assert_eq!(cells.iter().filter(|(c, _)| *c == (x, y)).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_map() {
+        let grid = Grid::from_str_with("123\n456\n", Ok).unwrap();
+        let digits = grid.map(|c| c.to_digit(10).unwrap());
+        assert_eq!((digits.width(), digits.height()), (3, 2));
+        assert_eq!(digits.get(1, 1), Some(&5));
+        assert_eq!(digits.iter_coords().map(|(_, d)| d).sum::<u32>(), 21);
+    }
+
+    #[test]
+    fn test_parse_sparse() {
+        #[derive(Debug, PartialEq, Eq)]
+        enum Tile {
+            Rock,
+            Tree,
+        }
+
+        let parse_tile = |c| match c {
+            '#' => Ok(Some(Tile::Rock)),
+            'T' => Ok(Some(Tile::Tree)),
+            '.' => Ok(None),
+            _ => Err(anyhow!("Invalid tile {:?}", c)),
+        };
+
+        let (width, height, tiles) = parse_sparse("#..\n.T.\n..#\n.T.\n", parse_tile).unwrap();
+        assert_eq!((width, height), (3, 4));
+        assert_eq!(
+            tiles,
+            HashMap::from([
+                ((0, 0), Tile::Rock),
+                ((1, 1), Tile::Tree),
+                ((2, 2), Tile::Rock),
+                ((1, 3), Tile::Tree),
+            ])
+        );
+        assert!(parse_sparse("#.?\n", parse_tile).is_err());
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut grid = example_grid();
+        *grid.get_mut(1, 2).unwrap() = 'x';
+        assert_eq!(grid.get(1, 2), Some(&'x'));
+        assert!(grid.get_mut(0, 3).is_none());
+    }
+
+    #[test]
+    fn test_neighbors4() {
+        let grid = example_grid();
+
+        let mut center = grid.neighbors4(1, 1).collect::<Vec<_>>();
+        center.sort();
+        assert_eq!(center, vec![(0, 1), (1, 0), (1, 2), (2, 1)]);
+
+        // Neighbors outside of the grid are not included
+        let mut corner = grid.neighbors4(2, 2).collect::<Vec<_>>();
+        corner.sort();
+        assert_eq!(corner, vec![(1, 2), (2, 1)]);
+        assert_eq!(grid.neighbors4(0, 1).count(), 3);
+    }
+
+    #[test]
+    fn test_neighbors8() {
+        let grid = example_grid();
+
+        let mut center = grid.neighbors8(1, 1).collect::<Vec<_>>();
+        center.sort();
+        assert_eq!(
+            center,
+            vec![
+                (0, 0),
+                (0, 1),
+                (0, 2),
+                (1, 0),
+                (1, 2),
+                (2, 0),
+                (2, 1),
+                (2, 2),
+            ]
+        );
+
+        // Neighbors outside of the grid are not included
+        assert_eq!(grid.neighbors8(0, 0).count(), 3);
+        assert_eq!(grid.neighbors8(2, 1).count(), 5);
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        // A 5x5 box with a wall splitting off the rightmost two columns
+        let wall = |(x, _): (isize, isize)| x == 2;
+        let bounds = Some(((0, 0), (4, 4)));
+        assert_eq!(flood_fill((0, 0), bounds, |c| !wall(c)).len(), 10);
+        assert_eq!(flood_fill((4, 4), bounds, |c| !wall(c)).len(), 10);
+        assert_eq!(flood_fill((0, 0), bounds, |_| true).len(), 25);
+
+        // Nothing is filled when starting on an impassable cell or outside the bounds
+        assert!(flood_fill((2, 0), bounds, |c| !wall(c)).is_empty());
+        assert!(flood_fill((5, 0), bounds, |_| true).is_empty());
+
+        // Without bounds the passable cells must enclose the fill
+        let diamond = flood_fill((0, 0), None, |(x, y)| x.abs() + y.abs() <= 2);
+        assert_eq!(diamond.len(), 13);
+    }
+}
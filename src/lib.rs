@@ -1,6 +1,16 @@
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
 #[macro_use]
 mod utils;
 
+pub mod direction;
+pub mod grid;
+pub mod math;
+pub mod rational;
+pub mod search;
+
 pub mod day1;
 pub mod day2;
 pub mod day3;
@@ -26,3 +36,309 @@ pub mod day20;
 pub mod day21;
 pub mod day22;
 pub mod day23;
+
+/// Days that take several seconds to solve
+pub const SLOW_DAYS: &[usize] = &[23];
+
+/// The answers to part A and B of a day as strings. An answer is missing when that part wasn't
+/// requested, or for part B on days where only part A is solved
+pub type Answers = (Option<String>, Option<String>);
+
+/// Which parts of a day to solve
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Part {
+    A,
+    B,
+    Both,
+}
+
+impl Part {
+    pub fn includes_a(self) -> bool {
+        self != Self::B
+    }
+
+    pub fn includes_b(self) -> bool {
+        self != Self::A
+    }
+}
+
+fn as_result<A: ToString, B: ToString>((a, b): (Option<A>, Option<B>)) -> Answers {
+    (
+        a.map(|answer| answer.to_string()),
+        b.map(|answer| answer.to_string()),
+    )
+}
+
+/// Log how long a step took, measured from the given instant. Only shown with --verbose
+#[cfg(feature = "verbose")]
+fn log_elapsed(step: &str, start: Instant) {
+    log::debug!("{} after {:?}", step, start.elapsed());
+}
+
+#[cfg(not(feature = "verbose"))]
+fn log_elapsed(_step: &str, _start: Instant) {}
+
+/// Parse the puzzle input and solve the requested parts one at a time, logging how long each step
+/// took
+fn solve_timed<'a, T, A: ToString, B: ToString>(
+    input: &'a str,
+    part: Part,
+    parse: impl FnOnce(&'a str) -> Result<T>,
+    solve: impl Fn(&T, Part) -> Result<(Option<A>, Option<B>)>,
+) -> Result<Answers> {
+    let start = Instant::now();
+    let parsed = parse(input)?;
+    log_elapsed("Parse complete", start);
+
+    let mut answers = (None, None);
+    if part.includes_a() {
+        let start = Instant::now();
+        answers.0 = as_result(solve(&parsed, Part::A)?).0;
+        log_elapsed("Part A done", start);
+    }
+    if part.includes_b() {
+        let start = Instant::now();
+        answers.1 = as_result(solve(&parsed, Part::B)?).1;
+        log_elapsed("Part B done", start);
+    }
+    Ok(answers)
+}
+
+/// Solve the requested parts of the given day using the puzzle input in the string
+pub fn solve(day: usize, input: &str, part: Part) -> Result<Answers> {
+    #[allow(
+        overlapping_range_endpoints,
+        unreachable_patterns,
+        clippy::match_overlapping_arm
+    )]
+    match day {
+        1 => solve_timed(input, part, day1::parse, |parsed, part| {
+            day1::solve(parsed, part)
+        }),
+        2 => solve_timed(input, part, day2::parse, |parsed, part| {
+            day2::solve(parsed, part)
+        }),
+        3 => solve_timed(input, part, day3::parse, day3::solve),
+        4 => solve_timed(input, part, day4::parse, |parsed, part| {
+            day4::solve(parsed, part)
+        }),
+        5 => solve_timed(input, part, day5::parse, day5::solve),
+        6 => solve_timed(input, part, day6::parse, |parsed, part| {
+            day6::solve(parsed, part)
+        }),
+        7 => solve_timed(input, part, day7::parse, |parsed, part| {
+            day7::solve(parsed, part)
+        }),
+        8 => solve_timed(input, part, day8::parse, day8::solve),
+        9 => solve_timed(input, part, day9::parse, |parsed, part| {
+            day9::solve(parsed, part)
+        }),
+        10 => solve_timed(input, part, day10::parse, day10::solve),
+        11 => solve_timed(input, part, day11::parse, day11::solve),
+        12 => solve_timed(input, part, day12::parse, |parsed, part| {
+            day12::solve(parsed, part)
+        }),
+        13 => solve_timed(input, part, day13::parse, |parsed, part| {
+            day13::solve(parsed, part)
+        }),
+        14 => solve_timed(input, part, day14::parse, day14::solve),
+        15 => solve_timed(input, part, day15::parse, |parsed, part| {
+            day15::solve(parsed, part)
+        }),
+        16 => solve_timed(input, part, day16::parse, day16::solve),
+        17 => solve_timed(input, part, day17::parse, day17::solve),
+        18 => solve_timed(input, part, day18::parse, day18::solve),
+        19 => solve_timed(input, part, day19::parse, day19::solve),
+        20 => solve_timed(input, part, day20::parse, day20::solve),
+        21 => solve_timed(input, part, day21::parse, day21::solve),
+        22 => solve_timed(input, part, day22::parse, |parsed, part| {
+            day22::solve(parsed, part)
+        }),
+        23 => solve_timed(input, part, day23::parse, day23::solve),
+        1..=25 => Err(anyhow!("No implementation for this day yet")),
+        day => Err(anyhow!("Day {} is not a valid day for advent of code", day)),
+    }
+}
+
+/// Solve day 11 where empty space expands by the given factor in part B, instead of by a million
+pub fn solve_day11_with_expansion(
+    input: &str,
+    part: Part,
+    expansion_factor: usize,
+) -> Result<Answers> {
+    solve_timed(input, part, day11::parse, |galaxies, part| {
+        day11::solve_with_expansion(galaxies, part, expansion_factor)
+    })
+}
+
+/// Return the parsed puzzle input of the given day formatted for debugging, without solving it
+pub fn dump_parse(day: usize, input: &str) -> Result<String> {
+    #[allow(
+        overlapping_range_endpoints,
+        unreachable_patterns,
+        clippy::match_overlapping_arm
+    )]
+    let parsed = match day {
+        1 => format!("{:#?}", day1::parse(input)?),
+        2 => format!("{:#?}", day2::parse(input)?),
+        3 => format!("{:#?}", day3::parse(input)?),
+        4 => format!("{:#?}", day4::parse(input)?),
+        5 => format!("{:#?}", day5::parse(input)?),
+        6 => format!("{:#?}", day6::parse(input)?),
+        7 => format!("{:#?}", day7::parse(input)?),
+        8 => format!("{:#?}", day8::parse(input)?),
+        9 => format!("{:#?}", day9::parse(input)?),
+        10 => format!("{:#?}", day10::parse(input)?),
+        11 => format!("{:#?}", day11::parse(input)?),
+        12 => format!("{:#?}", day12::parse(input)?),
+        13 => format!("{:#?}", day13::parse(input)?),
+        14 => format!("{:#?}", day14::parse(input)?),
+        15 => format!("{:#?}", day15::parse(input)?),
+        16 => format!("{:#?}", day16::parse(input)?),
+        17 => format!("{:#?}", day17::parse(input)?),
+        18 => format!("{:#?}", day18::parse(input)?),
+        19 => format!("{:#?}", day19::parse(input)?),
+        20 => format!("{:#?}", day20::parse(input)?),
+        21 => format!("{:#?}", day21::parse(input)?),
+        22 => format!("{:#?}", day22::parse(input)?),
+        23 => format!("{:#?}", day23::parse(input)?),
+        1..=25 => return Err(anyhow!("No implementation for this day yet")),
+        day => return Err(anyhow!("Day {} is not a valid day for advent of code", day)),
+    };
+    Ok(parsed)
+}
+
+fn time_solve<T, A, B>(
+    parsed: &T,
+    solve: impl Fn(&T, Part) -> Result<(Option<A>, Option<B>)>,
+    iterations: usize,
+) -> Result<Vec<Duration>> {
+    let mut timings = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let start = Instant::now();
+        solve(parsed, Part::Both)?;
+        timings.push(start.elapsed());
+    }
+    Ok(timings)
+}
+
+/// Parse the puzzle input once and then solve the given day the given number of times. Returns how
+/// long solving took for every iteration, not including the parsing
+pub fn bench(day: usize, input: &str, iterations: usize) -> Result<Vec<Duration>> {
+    #[allow(
+        overlapping_range_endpoints,
+        unreachable_patterns,
+        clippy::match_overlapping_arm
+    )]
+    match day {
+        1 => time_solve(
+            &day1::parse(input)?,
+            |parsed, part| day1::solve(parsed, part),
+            iterations,
+        ),
+        2 => time_solve(
+            &day2::parse(input)?,
+            |parsed, part| day2::solve(parsed, part),
+            iterations,
+        ),
+        3 => time_solve(&day3::parse(input)?, day3::solve, iterations),
+        4 => time_solve(
+            &day4::parse(input)?,
+            |parsed, part| day4::solve(parsed, part),
+            iterations,
+        ),
+        5 => time_solve(&day5::parse(input)?, day5::solve, iterations),
+        6 => time_solve(
+            &day6::parse(input)?,
+            |parsed, part| day6::solve(parsed, part),
+            iterations,
+        ),
+        7 => time_solve(
+            &day7::parse(input)?,
+            |parsed, part| day7::solve(parsed, part),
+            iterations,
+        ),
+        8 => time_solve(&day8::parse(input)?, day8::solve, iterations),
+        9 => time_solve(
+            &day9::parse(input)?,
+            |parsed, part| day9::solve(parsed, part),
+            iterations,
+        ),
+        10 => time_solve(&day10::parse(input)?, day10::solve, iterations),
+        11 => time_solve(&day11::parse(input)?, day11::solve, iterations),
+        12 => time_solve(
+            &day12::parse(input)?,
+            |parsed, part| day12::solve(parsed, part),
+            iterations,
+        ),
+        13 => time_solve(
+            &day13::parse(input)?,
+            |parsed, part| day13::solve(parsed, part),
+            iterations,
+        ),
+        14 => time_solve(&day14::parse(input)?, day14::solve, iterations),
+        15 => time_solve(
+            &day15::parse(input)?,
+            |parsed, part| day15::solve(parsed, part),
+            iterations,
+        ),
+        16 => time_solve(&day16::parse(input)?, day16::solve, iterations),
+        17 => time_solve(&day17::parse(input)?, day17::solve, iterations),
+        18 => time_solve(&day18::parse(input)?, day18::solve, iterations),
+        19 => time_solve(&day19::parse(input)?, day19::solve, iterations),
+        20 => time_solve(&day20::parse(input)?, day20::solve, iterations),
+        21 => time_solve(&day21::parse(input)?, day21::solve, iterations),
+        22 => time_solve(
+            &day22::parse(input)?,
+            |parsed, part| day22::solve(parsed, part),
+            iterations,
+        ),
+        23 => time_solve(&day23::parse(input)?, day23::solve, iterations),
+        1..=25 => Err(anyhow!("No implementation for this day yet")),
+        day => Err(anyhow!("Day {} is not a valid day for advent of code", day)),
+    }
+}
+
+/// Solve every day that has an input in the map, ordered by day
+pub fn solve_from_strings(inputs: &HashMap<usize, String>) -> Vec<(usize, Result<Answers>)> {
+    let mut days = inputs.keys().copied().collect::<Vec<_>>();
+    days.sort();
+    days.into_iter()
+        .map(|day| (day, solve(day, &inputs[&day], Part::Both)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_solve_from_strings() {
+        let inputs = HashMap::from([
+            (
+                6,
+                "Time:      7  15   30\nDistance:  9  40  200\n".to_string(),
+            ),
+            (
+                1,
+                "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet\n".to_string(),
+            ),
+            (30, String::new()),
+        ]);
+
+        let answers = solve_from_strings(&inputs);
+        assert_eq!(answers.len(), 3);
+        assert_eq!(answers[0].0, 1);
+        assert_eq!(
+            answers[0].1.as_ref().unwrap(),
+            &(Some("142".to_string()), Some("142".to_string()))
+        );
+        assert_eq!(answers[1].0, 6);
+        assert_eq!(
+            answers[1].1.as_ref().unwrap(),
+            &(Some("288".to_string()), Some("71503".to_string()))
+        );
+        assert_eq!(answers[2].0, 30);
+        assert!(answers[2].1.is_err());
+    }
+}
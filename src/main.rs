@@ -1,67 +1,704 @@
-use anyhow::{anyhow, Result};
-use clap::Parser;
-use std::path::PathBuf;
+use advent_of_code_2023::{Answers, Part, SLOW_DAYS};
+use anyhow::{anyhow, Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Format {
+    /// Labeled answers for humans
+    Text,
+    /// One `day,a,b` line per input
+    Csv,
+    /// One `{"day":1,"a":"...","b":"..."}` object per input
+    Json,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Solve a day repeatedly and print statistics of how long solving took
+    Bench {
+        /// The day to benchmark (1-25)
+        day: usize,
+
+        /// The input data file, or `-` to read from stdin. Will look for `data/day<num>.txt` by
+        /// default
+        input: Option<PathBuf>,
+
+        /// The number of times to solve the day
+        #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+        iters: u32,
+    },
+}
 
 #[derive(Debug, Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Options {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// The day to run the solution for (1-25)
-    day: usize,
+    #[arg(required = true)]
+    day: Option<usize>,
+
+    /// The input data files, or `-` to read from stdin. Will look for `data/day<num>.txt` by default
+    inputs: Vec<PathBuf>,
+
+    /// Fetch the input from the given URL instead of reading it from a file
+    #[cfg(feature = "download")]
+    #[arg(long, conflicts_with = "inputs")]
+    input_from_url: Option<String>,
+
+    /// Extra `Name: value` header to send when fetching the input, such as an auth token
+    #[cfg(feature = "download")]
+    #[arg(long, requires = "input_from_url")]
+    header: Vec<String>,
+
+    /// Validate the computed answers against the answers file
+    #[arg(long)]
+    check: bool,
+
+    /// File of expected answers with one `<day> <a> [b]` line per day
+    #[arg(long, default_value = "data/answers.txt")]
+    answers_file: PathBuf,
+
+    /// Only print the answers without any labels, one per line
+    #[arg(short, long, conflicts_with = "format")]
+    quiet: bool,
+
+    /// How to print the answers
+    #[arg(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
+
+    /// Which parts of the day to solve
+    #[arg(long, value_enum, default_value_t = Part::Both)]
+    part: Part,
+
+    /// Expansion factor of empty space in part B of day 11
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    expansion: Option<u64>,
 
-    /// The input data file. Will look for `data/day<num>.txt` by default
-    input: Option<PathBuf>,
+    /// Print how long reading the input and computing the answers took
+    #[arg(long, conflicts_with_all = ["quiet", "format", "dump_parse"])]
+    time: bool,
+
+    /// Don't warn before solving a day that is known to be slow
+    #[arg(long)]
+    no_warn: bool,
+
+    /// List the days that are known to be slow and exit
+    #[arg(long, exclusive = true)]
+    list_slow: bool,
+
+    /// Print the parsed puzzle input instead of solving it
+    #[arg(long, conflicts_with_all = ["check", "quiet"])]
+    dump_parse: bool,
+
+    /// Print progress and timing information for each step to stderr
+    #[cfg(feature = "verbose")]
+    #[arg(short, long)]
+    verbose: bool,
 }
 
 fn pad_newlines(answer: String) -> String {
     answer.lines().collect::<Vec<_>>().join("\n   ")
 }
 
-fn as_result<A: ToString, B: ToString>((a, b): (A, Option<B>)) -> (String, Option<String>) {
-    (a.to_string(), b.map(|answer| answer.to_string()))
+fn parse_answers(s: &str) -> Result<HashMap<usize, Answers>> {
+    let mut answers = HashMap::new();
+    for line in s.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split_whitespace();
+        let (Some(day_str), Some(a)) = (fields.next(), fields.next()) else {
+            return Err(anyhow!("Invalid answers line {:?}", line));
+        };
+        let day = day_str
+            .parse()
+            .map_err(|_| anyhow!("Invalid day {:?} in answers file", day_str))?;
+        answers.insert(
+            day,
+            (Some(a.to_string()), fields.next().map(ToOwned::to_owned)),
+        );
+    }
+    Ok(answers)
 }
 
-fn main() -> Result<()> {
-    let opts = Options::parse();
-    let input = opts
-        .input
-        .unwrap_or_else(|| format!("data/day{}.txt", opts.day).into());
-
-    #[allow(
-        overlapping_range_endpoints,
-        unreachable_patterns,
-        clippy::match_overlapping_arm
-    )]
-    let (a, b): (String, Option<String>) = match opts.day {
-        1 => as_result(advent_of_code_2023::day1::main(&input)?),
-        2 => as_result(advent_of_code_2023::day2::main(&input)?),
-        3 => as_result(advent_of_code_2023::day3::main(&input)?),
-        4 => as_result(advent_of_code_2023::day4::main(&input)?),
-        5 => as_result(advent_of_code_2023::day5::main(&input)?),
-        6 => as_result(advent_of_code_2023::day6::main(&input)?),
-        7 => as_result(advent_of_code_2023::day7::main(&input)?),
-        8 => as_result(advent_of_code_2023::day8::main(&input)?),
-        9 => as_result(advent_of_code_2023::day9::main(&input)?),
-        10 => as_result(advent_of_code_2023::day10::main(&input)?),
-        11 => as_result(advent_of_code_2023::day11::main(&input)?),
-        12 => as_result(advent_of_code_2023::day12::main(&input)?),
-        13 => as_result(advent_of_code_2023::day13::main(&input)?),
-        14 => as_result(advent_of_code_2023::day14::main(&input)?),
-        15 => as_result(advent_of_code_2023::day15::main(&input)?),
-        16 => as_result(advent_of_code_2023::day16::main(&input)?),
-        17 => as_result(advent_of_code_2023::day17::main(&input)?),
-        18 => as_result(advent_of_code_2023::day18::main(&input)?),
-        19 => as_result(advent_of_code_2023::day19::main(&input)?),
-        20 => as_result(advent_of_code_2023::day20::main(&input)?),
-        21 => as_result(advent_of_code_2023::day21::main(&input)?),
-        22 => as_result(advent_of_code_2023::day22::main(&input)?),
-        23 => as_result(advent_of_code_2023::day23::main(&input)?),
-        1..=25 => return Err(anyhow!("No implementation for this day yet")),
-        day => return Err(anyhow!("Day {} is not a valid day for advent of code", day)),
+/// Compare the answers of the solved parts against the expected answers. Parts without an expected
+/// answer are not checked
+fn check_answers(
+    day: usize,
+    part: Part,
+    (a, b): &Answers,
+    (expected_a, expected_b): &Answers,
+) -> Result<()> {
+    if part.includes_a() && expected_a.is_some() && a != expected_a {
+        return Err(anyhow!(
+            "Day {} part A is {}, expected {}",
+            day,
+            a.as_deref().unwrap_or("missing"),
+            expected_a.as_deref().unwrap_or_default(),
+        ));
+    }
+    if part.includes_b() && expected_b.is_some() && b != expected_b {
+        return Err(anyhow!(
+            "Day {} part B is {}, expected {}",
+            day,
+            b.as_deref().unwrap_or("missing"),
+            expected_b.as_deref().unwrap_or_default(),
+        ));
+    }
+    Ok(())
+}
+
+/// Read the puzzle input from the given file, or from stdin when the path is `-`
+fn read_input(path: &Path, stdin: &mut impl Read) -> Result<String> {
+    if path == Path::new("-") {
+        let mut input = String::new();
+        stdin.read_to_string(&mut input)?;
+        return Ok(input);
+    }
+
+    if !path.exists() {
+        return Err(anyhow!(
+            "Input file {} doesn't exist. Save your puzzle input there or pass the path to it as \
+             an argument",
+            path.display()
+        ));
+    }
+    Ok(std::fs::read_to_string(path)?)
+}
+
+/// Fetch the puzzle input from the given URL, sending every `Name: value` header along with the
+/// request
+#[cfg(feature = "download")]
+fn download_input(url: &str, headers: &[String]) -> Result<String> {
+    let mut request = ureq::get(url);
+    for header in headers {
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(anyhow!(
+                "Invalid header {:?}, expected `Name: value`",
+                header
+            ));
+        };
+        request = request.set(name.trim(), value.trim());
+    }
+    let response = request
+        .call()
+        .with_context(|| format!("Unable to fetch input from {}", url))?;
+    Ok(response.into_string()?)
+}
+
+fn default_input(day: usize) -> PathBuf {
+    format!("data/day{}.txt", day).into()
+}
+
+/// Parse the input once and then solve the day the given number of times, printing statistics of
+/// the time it took
+fn bench(
+    day: usize,
+    input: &Path,
+    iterations: u32,
+    stdin: &mut impl Read,
+    out: &mut impl Write,
+) -> Result<()> {
+    let input_str = read_input(input, stdin)?;
+    let mut timings = advent_of_code_2023::bench(day, &input_str, iterations.try_into()?)?;
+    timings.sort();
+
+    let median = if timings.len() % 2 == 0 {
+        (timings[timings.len() / 2 - 1] + timings[timings.len() / 2]) / 2
+    } else {
+        timings[timings.len() / 2]
+    };
+    let mean = timings.iter().sum::<Duration>() / iterations;
+
+    writeln!(out, "Solved day {} {} times", day, iterations)?;
+    writeln!(out, "min:    {:.1?}", timings[0])?;
+    writeln!(out, "median: {:.1?}", median)?;
+    writeln!(out, "mean:   {:.1?}", mean)?;
+    writeln!(out, "max:    {:.1?}", timings[timings.len() - 1])?;
+    Ok(())
+}
+
+#[cfg(feature = "verbose")]
+fn init_logging(verbose: bool) {
+    let level = if verbose {
+        log::LevelFilter::Debug
+    } else {
+        log::LevelFilter::Warn
     };
 
-    println!("A: {}", pad_newlines(a));
-    if let Some(b) = b {
-        println!("B: {}", pad_newlines(b));
+    // The logger may only be set once per process, which matters when testing
+    let _ = env_logger::Builder::new()
+        .filter_module("advent_of_code_2023", level)
+        .parse_default_env()
+        .try_init();
+}
+
+fn run(
+    opts: &Options,
+    stdin: &mut impl Read,
+    out: &mut impl Write,
+    err: &mut impl Write,
+) -> Result<()> {
+    #[cfg(feature = "verbose")]
+    init_logging(opts.verbose);
+
+    if let Some(Command::Bench { day, input, iters }) = &opts.command {
+        let input = input.clone().unwrap_or_else(|| default_input(*day));
+        return bench(*day, &input, *iters, stdin, out);
+    }
+
+    if opts.list_slow {
+        for day in SLOW_DAYS {
+            writeln!(out, "{}", day)?;
+        }
+        return Ok(());
+    }
+
+    let Some(day) = opts.day else {
+        return Err(anyhow!("No day given"));
+    };
+    if opts.expansion.is_some() && day != 11 {
+        return Err(anyhow!("--expansion is only supported for day 11"));
+    }
+    if SLOW_DAYS.contains(&day) && !opts.no_warn && !opts.dump_parse {
+        writeln!(
+            err,
+            "Day {} is slow and may take several seconds to solve. Pass --no-warn to hide this \
+             warning",
+            day
+        )?;
+    }
+    let inputs = if opts.inputs.is_empty() {
+        vec![default_input(day)]
+    } else {
+        opts.inputs.clone()
+    };
+
+    // The URL takes the place of the input path, which also makes it the label in messages
+    #[cfg(feature = "download")]
+    let inputs = match &opts.input_from_url {
+        Some(url) => vec![PathBuf::from(url)],
+        None => inputs,
+    };
+
+    let expected_answers = if opts.check {
+        let answers_str = std::fs::read_to_string(&opts.answers_file).with_context(|| {
+            format!(
+                "Unable to read answers file {}",
+                opts.answers_file.display()
+            )
+        })?;
+        let answers = parse_answers(&answers_str)?;
+        match answers.get(&day) {
+            Some(expected) => Some(expected.clone()),
+            None => return Err(anyhow!("No answers for day {} in answers file", day)),
+        }
+    } else {
+        None
+    };
+
+    for input in inputs.iter() {
+        let start = Instant::now();
+        #[cfg(feature = "download")]
+        let input_str = match &opts.input_from_url {
+            Some(url) => download_input(url, &opts.header)?,
+            None => read_input(input, stdin)?,
+        };
+        #[cfg(not(feature = "download"))]
+        let input_str = read_input(input, stdin)?;
+        let read_time = start.elapsed();
+
+        if opts.dump_parse {
+            writeln!(out, "{}", advent_of_code_2023::dump_parse(day, &input_str)?)?;
+            continue;
+        }
+
+        let start = Instant::now();
+        let answers = match opts.expansion {
+            Some(factor) => advent_of_code_2023::solve_day11_with_expansion(
+                &input_str,
+                opts.part,
+                factor.try_into()?,
+            ),
+            None => advent_of_code_2023::solve(day, &input_str, opts.part),
+        }
+        .with_context(|| format!("Failed to solve {}", input.display()))?;
+        let compute_time = start.elapsed();
+        if let Some(expected) = expected_answers.as_ref() {
+            check_answers(day, opts.part, &answers, expected)?;
+        }
+        let (a, b) = answers;
+
+        if opts.format == Format::Csv {
+            writeln!(
+                out,
+                "{},{},{}",
+                day,
+                a.unwrap_or_default(),
+                b.unwrap_or_default()
+            )?;
+            continue;
+        }
+
+        if opts.format == Format::Json {
+            writeln!(
+                out,
+                r#"{{"day":{},"a":{},"b":{}}}"#,
+                day,
+                serde_json::to_string(&a)?,
+                serde_json::to_string(&b)?,
+            )?;
+            continue;
+        }
+
+        if opts.quiet {
+            for answer in [a, b].into_iter().flatten() {
+                writeln!(out, "{}", answer)?;
+            }
+            continue;
+        }
+
+        // Label each block of answers when we're solving more than one input
+        if inputs.len() > 1 {
+            writeln!(out, "{}:", input.display())?;
+        }
+        if opts.time {
+            writeln!(out, "Read input in {:.1?}", read_time)?;
+        }
+        let labeled_answers = [("A", a), ("B", b)]
+            .into_iter()
+            .filter_map(|(label, answer)| Some((label, answer?)));
+        for (i, (label, answer)) in labeled_answers.enumerate() {
+            if opts.time && i == 0 {
+                writeln!(
+                    out,
+                    "{}: {} (compute {:.1?})",
+                    label,
+                    pad_newlines(answer),
+                    compute_time
+                )?;
+            } else {
+                writeln!(out, "{}: {}", label, pad_newlines(answer))?;
+            }
+        }
     }
 
     Ok(())
 }
+
+fn main() -> Result<()> {
+    let opts = Options::parse();
+    run(
+        &opts,
+        &mut std::io::stdin().lock(),
+        &mut std::io::stdout().lock(),
+        &mut std::io::stderr().lock(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Run with the given arguments and stdin, returning what was written to stdout and stderr
+    fn run_with_output(args: &[&str], stdin: &str) -> Result<(String, String)> {
+        let opts = Options::try_parse_from(["advent-of-code-2023"].iter().chain(args))?;
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        run(&opts, &mut stdin.as_bytes(), &mut out, &mut err)?;
+        Ok((String::from_utf8(out)?, String::from_utf8(err)?))
+    }
+
+    fn run_with_stdin(args: &[&str], stdin: &str) -> Result<String> {
+        Ok(run_with_output(args, stdin)?.0)
+    }
+
+    fn run_with_args(args: &[&str]) -> Result<String> {
+        run_with_stdin(args, "")
+    }
+
+    #[test]
+    fn test_missing_input() {
+        let err = run_with_args(&["1", "data/day1-missing.txt"]).unwrap_err();
+        assert!(err.to_string().contains("data/day1-missing.txt"));
+    }
+
+    #[test]
+    fn test_check_answers_file() {
+        let answers_path = std::env::temp_dir().join("advent-of-code-2023-answers.txt");
+        std::fs::write(&answers_path, "# day a b\n1 55090 54845\n6 1710720\n").unwrap();
+        let answers_arg = answers_path.to_str().unwrap();
+
+        let output = run_with_args(&["1", "--check", "--answers-file", answers_arg]).unwrap();
+        assert_eq!(output, "A: 55090\nB: 54845\n");
+
+        // Part B is not checked when it's missing from the answers file
+        assert!(run_with_args(&["6", "--check", "--answers-file", answers_arg]).is_ok());
+
+        let wrong_answers_path = std::env::temp_dir().join("advent-of-code-2023-wrong-answers.txt");
+        std::fs::write(&wrong_answers_path, "1 55090 12345\n").unwrap();
+        let err = run_with_args(&[
+            "1",
+            "--check",
+            "--answers-file",
+            wrong_answers_path.to_str().unwrap(),
+        ])
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Day 1 part B is 54845, expected 12345");
+
+        assert!(run_with_args(&["2", "--check", "--answers-file", answers_arg]).is_err());
+    }
+
+    #[test]
+    fn test_multiple_inputs() {
+        let example_path = std::env::temp_dir().join("advent-of-code-2023-day1-example.txt");
+        std::fs::write(
+            &example_path,
+            "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet\n",
+        )
+        .unwrap();
+
+        let output =
+            run_with_args(&["1", "data/day1.txt", example_path.to_str().unwrap()]).unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "data/day1.txt:\nA: 55090\nB: 54845\n{}:\nA: 142\nB: 142\n",
+                example_path.display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_stdin_input() {
+        let example = "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet\n";
+        assert_eq!(
+            run_with_stdin(&["1", "-"], example).unwrap(),
+            "A: 142\nB: 142\n"
+        );
+        assert_eq!(
+            run_with_stdin(
+                &["1", "-"],
+                &std::fs::read_to_string("data/day1.txt").unwrap()
+            )
+            .unwrap(),
+            run_with_args(&["1"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_part() {
+        assert_eq!(run_with_args(&["1", "--part", "a"]).unwrap(), "A: 55090\n");
+        assert_eq!(run_with_args(&["1", "--part", "b"]).unwrap(), "B: 54845\n");
+        assert_eq!(
+            run_with_args(&["1", "--part", "both"]).unwrap(),
+            run_with_args(&["1"]).unwrap()
+        );
+        assert_eq!(
+            run_with_args(&["1", "--part", "b", "--format", "csv"]).unwrap(),
+            "1,,54845\n"
+        );
+
+        // Only the solved part is checked
+        let answers_path = std::env::temp_dir().join("advent-of-code-2023-part-answers.txt");
+        std::fs::write(&answers_path, "1 55090 12345\n").unwrap();
+        let answers_arg = answers_path.to_str().unwrap();
+        assert!(
+            run_with_args(&["1", "--part", "a", "--check", "--answers-file", answers_arg]).is_ok()
+        );
+        assert!(
+            run_with_args(&["1", "--part", "b", "--check", "--answers-file", answers_arg]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_expansion() {
+        let example = concat!(
+            "...#......\n",
+            ".......#..\n",
+            "#.........\n",
+            "..........\n",
+            "......#...\n",
+            ".#........\n",
+            ".........#\n",
+            "..........\n",
+            ".......#..\n",
+            "#...#.....\n",
+        );
+        assert_eq!(
+            run_with_stdin(&["11", "-", "--expansion", "10"], example).unwrap(),
+            "A: 374\nB: 1030\n"
+        );
+        assert_eq!(
+            run_with_stdin(&["11", "-", "--expansion", "100", "-q"], example).unwrap(),
+            "374\n8410\n"
+        );
+
+        // Without the flag part B expands by a million
+        assert_eq!(
+            run_with_args(&["11", "--expansion", "1000000"]).unwrap(),
+            run_with_args(&["11"]).unwrap()
+        );
+        assert!(run_with_args(&["11", "--expansion", "0"]).is_err());
+        assert!(run_with_args(&["1", "--expansion", "10"]).is_err());
+    }
+
+    #[test]
+    fn test_quiet() {
+        assert_eq!(run_with_args(&["1", "--quiet"]).unwrap(), "55090\n54845\n");
+        assert_eq!(run_with_args(&["6", "-q"]).unwrap(), "1710720\n35349468\n");
+        assert!(run_with_args(&["1", "--quiet", "data/day1-missing.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_csv_format() {
+        assert_eq!(
+            run_with_args(&["1", "--format", "csv"]).unwrap(),
+            "1,55090,54845\n"
+        );
+        assert_eq!(
+            run_with_args(&["21", "--format", "csv"]).unwrap(),
+            "21,3615,\n"
+        );
+    }
+
+    #[test]
+    fn test_time() {
+        let output = run_with_args(&["1", "--time"]).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("Read input in "));
+        assert!(lines[1].starts_with("A: 55090 (compute "));
+        assert!(lines[1].ends_with(')'));
+        assert_eq!(lines[2], "B: 54845");
+        assert!(run_with_args(&["1", "--time", "--quiet"]).is_err());
+    }
+
+    #[test]
+    fn test_bench() {
+        let output = run_with_args(&["bench", "1", "--iters", "3"]).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "Solved day 1 3 times");
+        assert!(lines[1].starts_with("min: "));
+        assert!(lines[4].starts_with("max: "));
+
+        assert!(run_with_args(&["bench", "1", "--iters", "0"]).is_err());
+        assert!(run_with_args(&["bench", "1", "data/day1-missing.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_dump_parse() {
+        let output = run_with_args(&["19", "--dump-parse"]).unwrap();
+        assert!(output.contains("Workflow"));
+        assert!(!output.contains("A: "));
+        assert!(run_with_args(&["19", "--dump-parse", "--check"]).is_err());
+    }
+
+    #[test]
+    fn test_json_format() {
+        assert_eq!(
+            run_with_args(&["1", "--format", "json"]).unwrap(),
+            "{\"day\":1,\"a\":\"55090\",\"b\":\"54845\"}\n"
+        );
+
+        let output = run_with_args(&["21", "--format", "json"]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value["day"], 21);
+        assert_eq!(value["a"], "3615");
+        assert!(value["b"].is_null());
+    }
+
+    #[test]
+    fn test_slow_day_warning() {
+        // The example of day 23 is quick to solve even though the real input isn't
+        let example = concat!(
+            "#.###\n", //
+            "#...#\n", //
+            "###.#\n", //
+        );
+        let (out, err) = run_with_output(&["23", "-"], example).unwrap();
+        assert_eq!(out, "A: 4\nB: 4\n");
+        assert!(err.contains("Day 23 is slow"));
+        assert!(err.contains("--no-warn"));
+
+        let (out, err) = run_with_output(&["23", "-", "--no-warn"], example).unwrap();
+        assert_eq!(out, "A: 4\nB: 4\n");
+        assert_eq!(err, "");
+
+        // Fast days never warn
+        assert_eq!(run_with_output(&["1"], "").unwrap().1, "");
+    }
+
+    #[test]
+    fn test_list_slow() {
+        assert_eq!(run_with_args(&["--list-slow"]).unwrap(), "23\n");
+        assert!(run_with_args(&["1", "--list-slow"]).is_err());
+    }
+
+    #[cfg(feature = "download")]
+    #[test]
+    fn test_input_from_url() {
+        use std::io::BufRead;
+        use std::net::TcpListener;
+
+        // Serve the example of day 1 once and hand back the request headers that were received
+        let example = "1abc2\npqr3stu8vwx\na1b2c3d4e5f\ntreb7uchet\n";
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/day1.txt", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request_lines = Vec::new();
+            for line in std::io::BufReader::new(&stream).lines() {
+                let line = line.unwrap();
+                if line.is_empty() {
+                    break;
+                }
+                request_lines.push(line);
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
+                example.len(),
+                example
+            )
+            .unwrap();
+            request_lines
+        });
+
+        let output = run_with_args(&[
+            "1",
+            "--input-from-url",
+            &url,
+            "--header",
+            "Authorization: Bearer secret",
+        ])
+        .unwrap();
+        assert_eq!(output, run_with_stdin(&["1", "-"], example).unwrap());
+        assert_eq!(output, "A: 142\nB: 142\n");
+
+        let request_lines = server.join().unwrap();
+        assert!(request_lines[0].starts_with("GET /day1.txt "));
+        assert!(request_lines
+            .iter()
+            .any(|l| l == "Authorization: Bearer secret"));
+
+        assert!(run_with_args(&["1", "--input-from-url", &url, "data/day1.txt"]).is_err());
+        assert!(run_with_args(&["1", "--header", "Authorization: Bearer secret"]).is_err());
+    }
+
+    #[cfg(feature = "verbose")]
+    #[test]
+    fn test_verbose() {
+        let output = run_with_args(&["1", "--verbose"]).unwrap();
+        assert_eq!(output, run_with_args(&["1"]).unwrap());
+        assert_eq!(output, "A: 55090\nB: 54845\n");
+    }
+}
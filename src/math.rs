@@ -0,0 +1,58 @@
+pub fn gcd(mut a: usize, mut b: usize) -> usize {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+pub fn lcm(a: usize, b: usize) -> usize {
+    (a * b) / gcd(a, b)
+}
+
+/// Greatest common divisor of two signed integers. The result is never negative
+pub fn gcd_i(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple of two signed integers. The result is never negative
+pub fn lcm_i(a: i128, b: i128) -> i128 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    (a / gcd_i(a, b) * b).abs()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_gcd_lcm() {
+        assert_eq!(gcd(12, 8), 4);
+        assert_eq!(gcd(7, 0), 7);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(lcm(3, 5), 15);
+    }
+
+    #[test]
+    fn test_gcd_i() {
+        assert_eq!(gcd_i(-12, 8), 4);
+        assert_eq!(gcd_i(12, -8), 4);
+        assert_eq!(gcd_i(-12, -8), 4);
+        assert_eq!(gcd_i(0, -5), 5);
+        assert_eq!(gcd_i(0, 0), 0);
+    }
+
+    #[test]
+    fn test_lcm_i() {
+        assert_eq!(lcm_i(-4, 6), 12);
+        assert_eq!(lcm_i(4, -6), 12);
+        assert_eq!(lcm_i(-4, -6), 12);
+        assert_eq!(lcm_i(-3, 5), 15);
+        assert_eq!(lcm_i(0, -5), 0);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::math::gcd_i;
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+
+/// An exact fraction that is always kept in its lowest terms with a positive denominator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    /// Create a fraction and reduce it. Panics if the denominator is zero
+    pub fn new(num: i128, den: i128) -> Self {
+        assert!(den != 0, "Denominator of a rational must not be zero");
+        let divisor = gcd_i(num, den) * den.signum();
+        Self {
+            num: num / divisor,
+            den: den / divisor,
+        }
+    }
+
+    pub fn from_integer(n: i128) -> Self {
+        Self { num: n, den: 1 }
+    }
+
+    pub fn num(&self) -> i128 {
+        self.num
+    }
+
+    pub fn den(&self) -> i128 {
+        self.den
+    }
+
+    /// Return the value as an integer if the fraction has no remainder
+    pub fn to_integer(&self) -> Option<i128> {
+        (self.den == 1).then_some(self.num)
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(
+            self.num * other.den + other.num * self.den,
+            self.den * other.den,
+        )
+    }
+}
+
+impl Sub for Rational {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(
+            self.num * other.den - other.num * self.den,
+            self.den * other.den,
+        )
+    }
+}
+
+impl Mul for Rational {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(self.num * other.num, self.den * other.den)
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.num, self.den)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert_eq!(Rational::new(2, 4), Rational::new(1, 2));
+        assert_eq!(
+            (Rational::new(2, 4).num(), Rational::new(2, 4).den()),
+            (1, 2)
+        );
+        assert_eq!(Rational::new(3, -6), Rational::new(-1, 2));
+        assert_eq!(Rational::new(0, -5), Rational::from_integer(0));
+        assert_eq!(Rational::new(-8, -4).to_integer(), Some(2));
+        assert_eq!(Rational::new(1, 3).to_integer(), None);
+        assert_eq!(Rational::new(-6, 4).to_string(), "-3/2");
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let half = Rational::new(1, 2);
+        let third = Rational::new(1, 3);
+        assert_eq!(half + third, Rational::new(5, 6));
+        assert_eq!(half - third, Rational::new(1, 6));
+        assert_eq!(third - half, Rational::new(-1, 6));
+        assert_eq!(half * third, Rational::new(1, 6));
+        assert_eq!(
+            Rational::new(2, 3) * Rational::new(3, 2),
+            Rational::from_integer(1)
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// Node waiting to be expanded. Ordered so that the lowest estimated cost ends up at the top of a
+/// max-heap
+struct Candidate<N, C> {
+    estimated_cost: C,
+    cost: C,
+    node: N,
+}
+
+impl<N, C: Ord> PartialEq for Candidate<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<N, C: Ord> Eq for Candidate<N, C> {}
+
+impl<N, C: Ord> PartialOrd for Candidate<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> Ord for Candidate<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimated_cost.cmp(&self.estimated_cost)
+    }
+}
+
+/// Return the cost of the cheapest path from any of the start nodes to a node where `is_goal` is
+/// true. The heuristic estimates the remaining cost from a node and must never overestimate it,
+/// or the returned cost may not be the cheapest
+pub fn a_star<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut is_goal: impl FnMut(&N) -> bool,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+) -> Option<C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Default + Ord + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut to_visit = BinaryHeap::new();
+    let mut best_costs = HashMap::new();
+    for node in starts {
+        best_costs.insert(node.clone(), C::default());
+        to_visit.push(Candidate {
+            estimated_cost: heuristic(&node),
+            cost: C::default(),
+            node,
+        });
+    }
+
+    while let Some(Candidate { cost, node, .. }) = to_visit.pop() {
+        // We may already have found a cheaper way to this node after it was queued
+        if best_costs.get(&node).is_some_and(|&best| best < cost) {
+            continue;
+        }
+
+        if is_goal(&node) {
+            return Some(cost);
+        }
+
+        for (next, step_cost) in neighbors(&node) {
+            let next_cost = cost + step_cost;
+            if best_costs.get(&next).is_some_and(|&best| best <= next_cost) {
+                continue;
+            }
+            best_costs.insert(next.clone(), next_cost);
+            to_visit.push(Candidate {
+                estimated_cost: next_cost + heuristic(&next),
+                cost: next_cost,
+                node: next,
+            });
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_a_star() {
+        // An open 20x20 grid where every step costs one
+        let size = 20;
+        let target = (size - 1, 0);
+        let expand = |&(x, y): &(usize, usize)| {
+            [
+                x.checked_sub(1).map(|x| (x, y)),
+                y.checked_sub(1).map(|y| (x, y)),
+                (x + 1 < size).then_some((x + 1, y)),
+                (y + 1 < size).then_some((x, y + 1)),
+            ]
+            .into_iter()
+            .flatten()
+            .map(|n| (n, 1))
+            .collect::<Vec<_>>()
+        };
+
+        let mut num_expanded_guided = 0;
+        let guided = a_star(
+            [(0, 0)],
+            |&n| n == target,
+            |n| {
+                num_expanded_guided += 1;
+                expand(n)
+            },
+            |&(x, y): &(usize, usize)| x.abs_diff(target.0) + y.abs_diff(target.1),
+        );
+
+        let mut num_expanded_blind = 0;
+        let blind = a_star(
+            [(0, 0)],
+            |&n| n == target,
+            |n| {
+                num_expanded_blind += 1;
+                expand(n)
+            },
+            |_| 0,
+        );
+
+        assert_eq!(guided, Some(size - 1));
+        assert_eq!(blind, Some(size - 1));
+        assert!(num_expanded_guided < num_expanded_blind);
+
+        // There is no way out of the start
+        assert_eq!(
+            a_star([(0, 0)], |_| false, |_| Vec::new(), |_| 0usize),
+            None
+        );
+    }
+}
@@ -16,9 +16,9 @@ macro_rules! test_real_input {
             let expected_answer_b = Option::from($answer_b);
 
             if expected_answer_b.is_none() {
-                assert_eq!(a, expected_answer_a);
+                assert_eq!(a, Some(expected_answer_a));
             } else {
-                assert_eq!((a, b), (expected_answer_a, expected_answer_b));
+                assert_eq!((a, b), (Some(expected_answer_a), expected_answer_b));
             }
         }
     };